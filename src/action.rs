@@ -0,0 +1,201 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// A button which can trigger an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Any finger on the screen
+    Touch,
+    Android(AndroidKeyCode),
+    /// The button on any gamepad
+    Gamepad(GamepadButton),
+}
+
+impl Binding {
+    pub fn get_state(&self, input: &Input) -> ButtonState {
+        match *self {
+            Self::Key(key) => input.get_key(key),
+            Self::Mouse(button) => input.mouse.get_button(button),
+            Self::Touch => input.touch,
+            Self::Android(code) => input.android.get_button(code),
+            Self::Gamepad(button) => input.get_gamepad_button(button),
+        }
+    }
+}
+
+/// A continuous value read from the input of a frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AxisSource {
+    /// Pixels moved since the last update
+    MouseX,
+    MouseY,
+    /// Wheel steps since the last update
+    ScrollX,
+    ScrollY,
+    /// Pixels dragged by a single finger since the last update
+    TouchDragX,
+    TouchDragY,
+    /// Pinch scale since the last update minus 1, so it rests at 0
+    TouchPinch,
+    AndroidLeftX,
+    AndroidLeftY,
+    /// The axis of any gamepad, whichever is farthest from rest
+    Gamepad(GamepadAxis),
+}
+
+impl AxisSource {
+    pub fn get_value(&self, input: &Input) -> f32 {
+        match self {
+            Self::MouseX => input.mouse.movement.x,
+            Self::MouseY => input.mouse.movement.y,
+            Self::ScrollX => input.mouse.scroll.x,
+            Self::ScrollY => input.mouse.scroll.y,
+            Self::TouchDragX => input.touches.get_drag().x,
+            Self::TouchDragY => input.touches.get_drag().y,
+            Self::TouchPinch => input.touches.get_pinch() - 1.0,
+            Self::AndroidLeftX => input.android.left_axis.x,
+            Self::AndroidLeftY => input.android.left_axis.y,
+            Self::Gamepad(axis) => input.get_gamepad_axis(*axis),
+        }
+    }
+}
+
+/// Something which can drive an axis
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AxisBinding {
+    /// -1 while `negative` is held, 1 while `positive` is held, 0 for both or neither
+    Buttons {
+        negative: Binding,
+        positive: Binding,
+    },
+    Analog {
+        source: AxisSource,
+        scale: f32,
+    },
+}
+
+impl AxisBinding {
+    pub fn buttons(negative: Binding, positive: Binding) -> Self {
+        Self::Buttons { negative, positive }
+    }
+
+    pub fn analog(source: AxisSource) -> Self {
+        Self::Analog { source, scale: 1.0 }
+    }
+
+    /// Multiplies the value of an analog source, such as `-1.0` to invert it
+    pub fn scale(self, scale: f32) -> Self {
+        match self {
+            Self::Analog { source, .. } => Self::Analog { source, scale },
+            buttons => buttons,
+        }
+    }
+
+    pub fn get_value(&self, input: &Input) -> f32 {
+        match self {
+            Self::Buttons { negative, positive } => {
+                let positive = positive.get_state(input).is_down() as i32;
+                let negative = negative.get_state(input).is_down() as i32;
+                (positive - negative) as f32
+            }
+            Self::Analog { source, scale } => source.get_value(input) * scale,
+        }
+    }
+}
+
+/// Named actions and axes bound to raw input, so the same code runs with any device.
+/// Bindings can be changed at runtime and stored to a JSON file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionMap {
+    pub actions: BTreeMap<String, Vec<Binding>>,
+    pub axes: BTreeMap<String, Vec<AxisBinding>>,
+}
+
+impl ActionMap {
+    /// Adds a binding to an action
+    pub fn action<S: Into<String>>(mut self, name: S, binding: Binding) -> Self {
+        self.bind_action(name, binding);
+        self
+    }
+
+    /// Adds a binding to an axis
+    pub fn axis<S: Into<String>>(mut self, name: S, binding: AxisBinding) -> Self {
+        self.bind_axis(name, binding);
+        self
+    }
+
+    pub fn bind_action<S: Into<String>>(&mut self, name: S, binding: Binding) {
+        let bindings = self.actions.entry(name.into()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn bind_axis<S: Into<String>>(&mut self, name: S, binding: AxisBinding) {
+        self.axes.entry(name.into()).or_default().push(binding);
+    }
+
+    /// Replaces all the bindings of an action
+    pub fn rebind_action<S: Into<String>>(&mut self, name: S, bindings: Vec<Binding>) {
+        self.actions.insert(name.into(), bindings);
+    }
+
+    /// Replaces all the bindings of an axis
+    pub fn rebind_axis<S: Into<String>>(&mut self, name: S, bindings: Vec<AxisBinding>) {
+        self.axes.insert(name.into(), bindings);
+    }
+
+    /// Returns the most advanced state among the bindings of an action
+    pub fn get_action(&self, name: &str, input: &Input) -> ButtonState {
+        let Some(bindings) = self.actions.get(name) else {
+            return ButtonState::Released;
+        };
+        ButtonState::most_advanced(bindings.iter().map(|binding| binding.get_state(input)))
+    }
+
+    pub fn is_action_down(&self, name: &str, input: &Input) -> bool {
+        self.get_action(name, input).is_down()
+    }
+
+    /// Returns the sum of the values of all the bindings of an axis, 0 when unbound
+    pub fn get_axis(&self, name: &str, input: &Input) -> f32 {
+        self.axes
+            .get(name)
+            .map(|bindings| bindings.iter().map(|b| b.get_value(input)).sum())
+            .unwrap_or_default()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize action map")
+    }
+
+    pub fn try_from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        Self::try_load(path).expect("Failed to load action map")
+    }
+
+    pub fn try_load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::try_from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) {
+        self.try_save(path).expect("Failed to save action map")
+    }
+
+    pub fn try_save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        Ok(std::fs::write(path, self.to_json())?)
+    }
+}
@@ -13,6 +13,8 @@ pub struct RenderBuffer {
     allocation: vk_mem::Allocation,
     pub buffer: vk::Buffer,
     usage: vk::BufferUsageFlags,
+    /// How the host is going to access this buffer
+    host_access: vk_mem::AllocationCreateFlags,
     pub size: vk::DeviceSize,
     pub allocator: Arc<Allocator>,
 }
@@ -22,7 +24,8 @@ impl RenderBuffer {
         allocator: &vk_mem::Allocator,
         size: vk::DeviceSize,
         usage: vk::BufferUsageFlags,
-    ) -> (vk::Buffer, vk_mem::Allocation) {
+        host_access: vk_mem::AllocationCreateFlags,
+    ) -> Result<(vk::Buffer, vk_mem::Allocation)> {
         let buffer_info = vk::BufferCreateInfo::default()
             // Minimum size is 16 bytes
             .size(size.max(16))
@@ -32,17 +35,16 @@ impl RenderBuffer {
         // Vulkan memory
         let create_info = vk_mem::AllocationCreateInfo {
             usage: vk_mem::MemoryUsage::Auto,
-            flags: vk_mem::AllocationCreateFlags::HOST_ACCESS_SEQUENTIAL_WRITE,
+            flags: host_access,
             required_flags: vk::MemoryPropertyFlags::HOST_VISIBLE,
             preferred_flags: vk::MemoryPropertyFlags::HOST_COHERENT
                 | vk::MemoryPropertyFlags::HOST_CACHED,
             ..Default::default()
         };
 
-        let (buffer, allocation) = unsafe { allocator.create_buffer(&buffer_info, &create_info) }
-            .expect("Failed to create Vulkan buffer");
+        let (buffer, allocation) = unsafe { allocator.create_buffer(&buffer_info, &create_info) }?;
 
-        (buffer, allocation)
+        Ok((buffer, allocation))
     }
 
     pub fn new_with_size(
@@ -50,15 +52,50 @@ impl RenderBuffer {
         usage: vk::BufferUsageFlags,
         size: vk::DeviceSize,
     ) -> Self {
-        let (buffer, allocation) = Self::create_buffer(allocator, size, usage);
+        Self::try_new_with_size(allocator, usage, size).expect("Failed to create Vulkan buffer")
+    }
 
-        Self {
+    pub fn try_new_with_size(
+        allocator: &Arc<Allocator>,
+        usage: vk::BufferUsageFlags,
+        size: vk::DeviceSize,
+    ) -> Result<Self> {
+        Self::new_with_host_access(
+            allocator,
+            usage,
+            size,
+            vk_mem::AllocationCreateFlags::HOST_ACCESS_SEQUENTIAL_WRITE,
+        )
+    }
+
+    fn new_with_host_access(
+        allocator: &Arc<Allocator>,
+        usage: vk::BufferUsageFlags,
+        size: vk::DeviceSize,
+        host_access: vk_mem::AllocationCreateFlags,
+    ) -> Result<Self> {
+        let (buffer, allocation) = Self::create_buffer(allocator, size, usage, host_access)?;
+
+        Ok(Self {
             allocation,
             buffer,
             size,
             usage,
+            host_access,
             allocator: allocator.clone(),
-        }
+        })
+    }
+
+    /// Creates a buffer which can be used as the destination of a transfer
+    /// and then read back from the host
+    pub fn readback(allocator: &Arc<Allocator>, size: vk::DeviceSize) -> Self {
+        Self::new_with_host_access(
+            allocator,
+            vk::BufferUsageFlags::TRANSFER_DST,
+            size,
+            vk_mem::AllocationCreateFlags::HOST_ACCESS_RANDOM,
+        )
+        .expect("Failed to create Vulkan buffer")
     }
 
     pub fn new<T>(allocator: &Arc<Allocator>, usage: vk::BufferUsageFlags) -> Self {
@@ -66,23 +103,46 @@ impl RenderBuffer {
         Self::new_with_size(allocator, usage, size)
     }
 
+    pub fn try_new<T>(allocator: &Arc<Allocator>, usage: vk::BufferUsageFlags) -> Result<Self> {
+        let size = std::mem::size_of::<T>() as vk::DeviceSize;
+        Self::try_new_with_size(allocator, usage, size)
+    }
+
     pub fn from_data(allocator: &Arc<Allocator>, data: &[u8], usage: vk::BufferUsageFlags) -> Self {
-        let mut buffer = Self::new_with_size(allocator, usage, data.len() as vk::DeviceSize);
+        Self::try_from_data(allocator, data, usage).expect("Failed to create Vulkan buffer")
+    }
+
+    pub fn try_from_data(
+        allocator: &Arc<Allocator>,
+        data: &[u8],
+        usage: vk::BufferUsageFlags,
+    ) -> Result<Self> {
+        let mut buffer = Self::try_new_with_size(allocator, usage, data.len() as vk::DeviceSize)?;
         buffer.upload_arr(data);
-        buffer
+        Ok(buffer)
     }
 
     /// Loads data from a png image in `path` directly into a staging buffer
     pub fn load(allocator: &Arc<Allocator>, image: ::image::RgbaImage) -> Self {
+        Self::try_load(allocator, image).expect("Failed to create Vulkan staging buffer")
+    }
+
+    pub fn try_load(allocator: &Arc<Allocator>, image: ::image::RgbaImage) -> Result<Self> {
         let size = image.len();
         let usage = vk::BufferUsageFlags::TRANSFER_SRC;
+        let host_access = vk_mem::AllocationCreateFlags::HOST_ACCESS_SEQUENTIAL_WRITE;
 
         // Create staging buffer
         let (buffer, mut allocation) =
-            Self::create_buffer(allocator, size as vk::DeviceSize, usage);
-
-        let data =
-            unsafe { allocator.map_memory(&mut allocation) }.expect("Failed to map Vulkan memory");
+            Self::create_buffer(allocator, size as vk::DeviceSize, usage, host_access)?;
+
+        let data = match unsafe { allocator.map_memory(&mut allocation) } {
+            Ok(data) => data,
+            Err(result) => {
+                unsafe { allocator.destroy_buffer(buffer, &mut allocation) };
+                return Err(result.into());
+            }
+        };
 
         // Allocate the output buffer
         let buf = unsafe { std::slice::from_raw_parts_mut(data, size) };
@@ -92,13 +152,14 @@ impl RenderBuffer {
 
         unsafe { allocator.unmap_memory(&mut allocation) };
 
-        Self {
+        Ok(Self {
             allocation,
             buffer,
             usage,
+            host_access,
             size: size as vk::DeviceSize,
             allocator: allocator.clone(),
-        }
+        })
     }
 
     pub fn upload<T>(&mut self, data: &T) {
@@ -122,13 +183,25 @@ impl RenderBuffer {
             };
 
             self.size = size;
-            let (buffer, allocation) = Self::create_buffer(&self.allocator, size, self.usage);
+            let (buffer, allocation) =
+                Self::create_buffer(&self.allocator, size, self.usage, self.host_access)
+                    .expect("Failed to create Vulkan buffer");
             self.buffer = buffer;
             self.allocation = allocation;
         }
 
         self.upload_raw(arr.as_ptr(), size);
     }
+
+    /// Copies the whole content of this buffer into host memory
+    pub fn download(&mut self) -> Vec<u8> {
+        let mut ret = vec![0; self.size as usize];
+        let data = unsafe { self.allocator.map_memory(&mut self.allocation) }
+            .expect("Failed to map Vulkan memory");
+        unsafe { data.copy_to(ret.as_mut_ptr(), ret.len()) };
+        unsafe { self.allocator.unmap_memory(&mut self.allocation) };
+        ret
+    }
 }
 
 impl Drop for RenderBuffer {
@@ -39,7 +39,14 @@ impl CommandBuffer {
         .expect("Failed to begin Vulkan command buffer");
     }
 
-    pub fn begin_render_pass(&self, pass: &Pass, framebuffer: &Framebuffer, area: Size2) {
+    /// Begins `pass`, clearing its attachments to `clear_values`, see `Pass::get_clear_values`
+    pub fn begin_render_pass(
+        &self,
+        pass: &Pass,
+        framebuffer: &Framebuffer,
+        area: Size2,
+        clear_values: &[vk::ClearValue],
+    ) {
         let area = vk::Rect2D::default()
             .offset(vk::Offset2D::default().x(0).y(0))
             .extent(
@@ -48,25 +55,11 @@ impl CommandBuffer {
                     .height(area.height),
             );
 
-        let mut present_clear = vk::ClearValue::default();
-        present_clear.color.float32 = [0.0, 10.0 / 255.0, 28.0 / 255.0, 1.0];
-
-        let mut depth_clear = vk::ClearValue::default();
-        depth_clear.depth_stencil.depth = 0.0;
-        depth_clear.depth_stencil.stencil = 0;
-
-        let mut color_clear = vk::ClearValue::default();
-        color_clear.color.float32 = [0.2, 0.0, 0.1, 1.0];
-
-        let mut normal_clear = vk::ClearValue::default();
-        normal_clear.color.float32 = [0.0, 0.0, 0.0, 1.0];
-
-        let clear_values = [present_clear, depth_clear, color_clear, normal_clear];
         let create_info = vk::RenderPassBeginInfo::default()
             .framebuffer(framebuffer.framebuffer)
             .render_pass(pass.render)
             .render_area(area)
-            .clear_values(&clear_values);
+            .clear_values(clear_values);
         // Record it in the main command buffer
         let contents = vk::SubpassContents::INLINE;
         unsafe {
@@ -75,6 +68,30 @@ impl CommandBuffer {
         };
     }
 
+    /// Begins a render pass with a framebuffer which is not a `Framebuffer`,
+    /// such as a layer of the shadow atlas
+    pub fn begin_depth_render_pass(&self, pass: &Pass, framebuffer: vk::Framebuffer, area: Size2) {
+        let area = vk::Rect2D::default().extent(
+            vk::Extent2D::default()
+                .width(area.width)
+                .height(area.height),
+        );
+
+        let clear_values = pass.get_clear_values(None);
+        let create_info = vk::RenderPassBeginInfo::default()
+            .framebuffer(framebuffer)
+            .render_pass(pass.render)
+            .render_area(area)
+            .clear_values(&clear_values);
+        unsafe {
+            self.device.cmd_begin_render_pass(
+                self.command_buffer,
+                &create_info,
+                vk::SubpassContents::INLINE,
+            )
+        };
+    }
+
     pub fn next_subpass(&self) {
         unsafe {
             self.device
@@ -82,6 +99,18 @@ impl CommandBuffer {
         };
     }
 
+    /// Clears `attachments` of the current subpass within `rect`
+    pub fn clear_attachments(&self, attachments: &[vk::ClearAttachment], rect: vk::Rect2D) {
+        let rect = vk::ClearRect::default()
+            .rect(rect)
+            .base_array_layer(0)
+            .layer_count(1);
+        unsafe {
+            self.device
+                .cmd_clear_attachments(self.command_buffer, attachments, &[rect])
+        };
+    }
+
     pub fn set_viewport(&self, viewport: vk::Viewport) {
         unsafe {
             self.device
@@ -229,6 +258,45 @@ impl CommandBuffer {
             );
         }
     }
+
+    pub fn blit_image(
+        &self,
+        src: &RenderImage,
+        src_layout: vk::ImageLayout,
+        dst: &RenderImage,
+        dst_layout: vk::ImageLayout,
+        region: &vk::ImageBlit,
+        filter: vk::Filter,
+    ) {
+        unsafe {
+            self.device.cmd_blit_image(
+                self.command_buffer,
+                src.image,
+                src_layout,
+                dst.image,
+                dst_layout,
+                &[*region],
+                filter,
+            );
+        }
+    }
+
+    pub fn copy_image_to_buffer(
+        &self,
+        image: &RenderImage,
+        buffer: &RenderBuffer,
+        region: &vk::BufferImageCopy,
+    ) {
+        unsafe {
+            self.device.cmd_copy_image_to_buffer(
+                self.command_buffer,
+                image.image,
+                image.layout,
+                buffer.buffer,
+                &[*region],
+            );
+        }
+    }
 }
 
 impl Drop for CommandBuffer {
@@ -247,9 +315,13 @@ pub struct CommandPool {
 
 impl CommandPool {
     pub fn new(device: &Device) -> Self {
+        Self::new_with_family(device, device.graphics_queue_index)
+    }
+
+    pub fn new_with_family(device: &Device, family_index: u32) -> Self {
         let create_info = vk::CommandPoolCreateInfo::default()
             .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
-            .queue_family_index(device.graphics_queue_index);
+            .queue_family_index(family_index);
 
         let pool = {
             unsafe {
@@ -0,0 +1,356 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use std::f32::consts::FRAC_PI_2;
+use std::time::Duration;
+
+use crate::*;
+
+/// Keeps pitch slightly away from the poles, where yaw would become ambiguous
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// Moves a camera node from the input of a frame.
+/// Mouse movement is already relative to the previous frame, while keys are held for `delta`,
+/// so both result in the same motion regardless of the frame rate.
+pub trait CameraController {
+    fn update(&mut self, input: &Input, delta: Duration, trs: &mut Trs);
+
+    /// Updates the default camera node of `scene`
+    fn update_scene(&mut self, input: &Input, delta: Duration, scene: &mut RenderScene) {
+        self.update(input, delta, &mut scene.get_default_camera_node_mut().trs);
+    }
+}
+
+/// Returns the direction a camera looks at, its `-Z` axis, after `yaw` around `Y` and `pitch` around `X`
+fn get_forward(yaw: f32, pitch: f32) -> Vec3 {
+    Vec3::new(
+        -yaw.sin() * pitch.cos(),
+        pitch.sin(),
+        -yaw.cos() * pitch.cos(),
+    )
+}
+
+/// Returns the `X` axis of a camera after `yaw` around `Y`
+fn get_right(yaw: f32) -> Vec3 {
+    Vec3::new(yaw.cos(), 0.0, -yaw.sin())
+}
+
+/// Returns the rotation of `yaw` around `Y` applied after `pitch` around `X`
+fn get_rotation(yaw: f32, pitch: f32) -> Quat {
+    Quat::axis_angle(Vec3::Y_AXIS, yaw) * Quat::axis_angle(Vec3::X_AXIS, pitch)
+}
+
+/// Returns the yaw and pitch of a camera rotated by `rotation`, ignoring its roll
+fn get_yaw_and_pitch(rotation: Quat) -> (f32, f32) {
+    let forward = rotation * -Vec3::Z_AXIS;
+    let yaw = (-forward.x).atan2(-forward.z);
+    let pitch = forward.y.clamp(-1.0, 1.0).asin();
+    (yaw, pitch.clamp(-MAX_PITCH, MAX_PITCH))
+}
+
+fn get_trs(position: Vec3, yaw: f32, pitch: f32) -> Trs {
+    Trs::builder()
+        .translation(position)
+        .rotation(get_rotation(yaw, pitch))
+        .build()
+}
+
+/// Returns -1, 0, or 1 depending on which of the two keys is held
+fn get_axis(input: &Input, negative: KeyCode, positive: KeyCode) -> f32 {
+    input.is_key_down(positive) as i32 as f32 - input.is_key_down(negative) as i32 as f32
+}
+
+/// Returns the movement of the mouse while `button` is held, plus the one of a dragging finger
+fn get_drag(input: &Input, button: MouseButton) -> Vec2 {
+    let mut drag = input.touches.get_drag();
+    if input.mouse.is_down(button) {
+        drag.x += input.mouse.movement.x;
+        drag.y += input.mouse.movement.y;
+    }
+    drag
+}
+
+/// Returns the factor to multiply a distance by, after scrolling the wheel or pinching
+fn get_zoom(input: &Input, sensitivity: f32) -> f32 {
+    (1.0 - sensitivity).powf(input.mouse.scroll.y) / input.touches.get_pinch()
+}
+
+/// Rotates around a target while dragging with the left button or a finger,
+/// pans the target with the right button, and zooms with the mouse wheel or pinching
+pub struct OrbitController {
+    pub target: Vec3,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+
+    /// Radians per pixel of mouse movement
+    pub rotate_sensitivity: f32,
+
+    /// Fraction of the distance panned per pixel of mouse movement
+    pub pan_sensitivity: f32,
+
+    /// Fraction of the distance zoomed per wheel step
+    pub zoom_sensitivity: f32,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self {
+            target: Vec3::default(),
+            distance: 4.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            min_distance: 0.1,
+            max_distance: 1000.0,
+            rotate_sensitivity: 0.005,
+            pan_sensitivity: 0.002,
+            zoom_sensitivity: 0.1,
+        }
+    }
+}
+
+impl OrbitController {
+    /// Creates a controller orbiting around the point `distance` in front of a camera node,
+    /// keeping its current position, yaw and pitch
+    pub fn from_trs(trs: &Trs, distance: f32) -> Self {
+        let (yaw, pitch) = get_yaw_and_pitch(trs.rotation);
+        Self {
+            target: trs.translation + get_forward(yaw, pitch) * distance,
+            distance,
+            yaw,
+            pitch,
+            ..Default::default()
+        }
+    }
+
+    pub fn target(mut self, x: f32, y: f32, z: f32) -> Self {
+        self.target = Vec3::new(x, y, z);
+        self
+    }
+
+    pub fn distance(mut self, distance: f32) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    pub fn yaw(mut self, yaw: f32) -> Self {
+        self.yaw = yaw;
+        self
+    }
+
+    pub fn pitch(mut self, pitch: f32) -> Self {
+        self.pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        self
+    }
+
+    pub fn sensitivity(mut self, rotate: f32, pan: f32, zoom: f32) -> Self {
+        self.rotate_sensitivity = rotate;
+        self.pan_sensitivity = pan;
+        self.zoom_sensitivity = zoom;
+        self
+    }
+
+    /// Returns the position of the camera looking at the target
+    pub fn get_position(&self) -> Vec3 {
+        self.target - get_forward(self.yaw, self.pitch) * self.distance
+    }
+
+    pub fn get_trs(&self) -> Trs {
+        get_trs(self.get_position(), self.yaw, self.pitch)
+    }
+}
+
+impl CameraController for OrbitController {
+    fn update(&mut self, input: &Input, _delta: Duration, trs: &mut Trs) {
+        let drag = get_drag(input, MouseButton::Left);
+        self.yaw -= drag.x * self.rotate_sensitivity;
+        self.pitch = (self.pitch - drag.y * self.rotate_sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+
+        let movement = &input.mouse.movement;
+        if input.mouse.is_down(MouseButton::Right) {
+            // Drag the target along with the cursor, faster when far away
+            let scale = self.distance * self.pan_sensitivity;
+            let right = get_right(self.yaw);
+            let forward = get_forward(self.yaw, self.pitch);
+            let up = right.cross(&forward);
+            self.target += (up * movement.y - right * movement.x) * scale;
+        }
+        let zoom = get_zoom(input, self.zoom_sensitivity);
+        self.distance = (self.distance * zoom).clamp(self.min_distance, self.max_distance);
+        *trs = self.get_trs();
+    }
+}
+
+/// First-person camera which looks around while dragging with the right button or a finger,
+/// moves with W/A/S/D, and descends or ascends with Q/E
+pub struct FlyController {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+
+    /// Units per second
+    pub speed: f32,
+
+    /// Radians per pixel of mouse movement
+    pub look_sensitivity: f32,
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        Self {
+            position: Vec3::default(),
+            yaw: 0.0,
+            pitch: 0.0,
+            speed: 2.0,
+            look_sensitivity: 0.003,
+        }
+    }
+}
+
+impl FlyController {
+    /// Creates a controller starting from the current position, yaw and pitch of a camera node
+    pub fn from_trs(trs: &Trs) -> Self {
+        let (yaw, pitch) = get_yaw_and_pitch(trs.rotation);
+        Self {
+            position: trs.translation,
+            yaw,
+            pitch,
+            ..Default::default()
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32, z: f32) -> Self {
+        self.position = Vec3::new(x, y, z);
+        self
+    }
+
+    pub fn yaw(mut self, yaw: f32) -> Self {
+        self.yaw = yaw;
+        self
+    }
+
+    pub fn pitch(mut self, pitch: f32) -> Self {
+        self.pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        self
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn sensitivity(mut self, look: f32) -> Self {
+        self.look_sensitivity = look;
+        self
+    }
+
+    pub fn get_trs(&self) -> Trs {
+        get_trs(self.position, self.yaw, self.pitch)
+    }
+}
+
+impl CameraController for FlyController {
+    fn update(&mut self, input: &Input, delta: Duration, trs: &mut Trs) {
+        let drag = get_drag(input, MouseButton::Right);
+        self.yaw -= drag.x * self.look_sensitivity;
+        self.pitch = (self.pitch - drag.y * self.look_sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+
+        let step = self.speed * delta.as_secs_f32();
+        let forward = get_forward(self.yaw, self.pitch);
+        let right = get_right(self.yaw);
+        let forward_amount = get_axis(input, KeyCode::KeyS, KeyCode::KeyW) * step;
+        let right_amount = get_axis(input, KeyCode::KeyA, KeyCode::KeyD) * step;
+        let up_amount = get_axis(input, KeyCode::KeyQ, KeyCode::KeyE) * step;
+        self.position += forward * forward_amount + right * right_amount;
+        self.position += Vec3::Y_AXIS * up_amount;
+
+        *trs = self.get_trs();
+    }
+}
+
+/// Camera looking down its `-Z` axis at the `XY` plane, panned while dragging with the left button
+/// and zoomed with the mouse wheel. Orthographic cameras need their projection from `get_projection()`,
+/// as moving them along `Z` does not change what they see
+pub struct PanController {
+    pub position: Vec2,
+
+    /// Distance from the `XY` plane
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+
+    /// Scene units per pixel of mouse movement at a distance of 1
+    pub pan_sensitivity: f32,
+
+    /// Fraction of the distance zoomed per wheel step
+    pub zoom_sensitivity: f32,
+}
+
+impl Default for PanController {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            distance: 4.0,
+            min_distance: 0.1,
+            max_distance: 1000.0,
+            pan_sensitivity: 0.002,
+            zoom_sensitivity: 0.1,
+        }
+    }
+}
+
+impl PanController {
+    /// Creates a controller starting from the current position of a camera node
+    pub fn from_trs(trs: &Trs) -> Self {
+        Self {
+            position: Vec2::new(trs.translation.x, trs.translation.y),
+            distance: trs.translation.z,
+            ..Default::default()
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Vec2::new(x, y);
+        self
+    }
+
+    pub fn distance(mut self, distance: f32) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    pub fn sensitivity(mut self, pan: f32, zoom: f32) -> Self {
+        self.pan_sensitivity = pan;
+        self.zoom_sensitivity = zoom;
+        self
+    }
+
+    /// Returns an orthographic projection showing as much of the plane
+    /// as a vertical field of view of `yfov` would at the current distance
+    pub fn get_projection(&self, yfov: f32) -> CameraProjection {
+        let ymag = self.distance * (yfov * 0.5).tan();
+        CameraProjection::orthographic(ymag, 0.0, self.distance * 2.0)
+    }
+
+    pub fn get_trs(&self) -> Trs {
+        let position = Vec3::new(self.position.x, self.position.y, self.distance);
+        get_trs(position, 0.0, 0.0)
+    }
+}
+
+impl CameraController for PanController {
+    fn update(&mut self, input: &Input, _delta: Duration, trs: &mut Trs) {
+        // Window coordinates grow downwards, while scene ones grow upwards
+        let drag = get_drag(input, MouseButton::Left);
+        let scale = self.distance * self.pan_sensitivity;
+        self.position.x -= drag.x * scale;
+        self.position.y += drag.y * scale;
+
+        let zoom = get_zoom(input, self.zoom_sensitivity);
+        self.distance = (self.distance * zoom).clamp(self.min_distance, self.max_distance);
+        *trs = self.get_trs();
+    }
+}
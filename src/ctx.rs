@@ -6,11 +6,13 @@ use std::ffi::{CString, c_char};
 
 use ash::{ext, khr, vk};
 
-use crate::Win;
+use crate::{DeviceSelector, PhysicalDeviceInfo, Result, Win};
 
 pub struct Ctx {
     pub entry: ash::Entry,
     pub instance: ash::Instance,
+    /// Used to choose the physical device when creating a `Dev`
+    pub device_selector: DeviceSelector,
 }
 
 impl Ctx {
@@ -19,13 +21,17 @@ impl Ctx {
     }
 
     pub fn new(extensions_names: &[*const c_char]) -> Self {
+        Self::try_new(extensions_names).expect("Failed to create Vulkan context")
+    }
+
+    pub fn try_new(extensions_names: &[*const c_char]) -> Result<Self> {
         let mut layers = vec![];
         if cfg!(not(target_os = "android")) {
             layers.push(CString::new("VK_LAYER_KHRONOS_validation").unwrap());
         }
         let layer_names: Vec<*const c_char> = layers.iter().map(|name| name.as_ptr()).collect();
 
-        let entry = unsafe { ash::Entry::load() }.expect("Failed to create ash entry");
+        let entry = unsafe { ash::Entry::load() }?;
         let app_info = vk::ApplicationInfo {
             p_application_name: "Test" as *const str as _,
             api_version: vk::make_api_version(0, 1, 3, 0),
@@ -45,10 +51,18 @@ impl Ctx {
         #[cfg(target_os = "macos")]
         let create_info = create_info.flags(vk::InstanceCreateFlags::ENUMERATE_PORTABILITY_KHR);
 
-        let instance = unsafe { entry.create_instance(&create_info, None) }
-            .expect("Failed to create Vulkan instance");
+        let instance = unsafe { entry.create_instance(&create_info, None) }?;
 
-        Self { entry, instance }
+        Ok(Self {
+            entry,
+            instance,
+            device_selector: DeviceSelector::default(),
+        })
+    }
+
+    /// Lists all the physical devices, so that tools can show them
+    pub fn get_physical_devices(&self) -> Result<Vec<PhysicalDeviceInfo>> {
+        PhysicalDeviceInfo::enumerate(&self.instance)
     }
 }
 
@@ -63,6 +77,7 @@ impl Drop for Ctx {
 pub struct CtxBuilder<'w> {
     debug: bool,
     win: Option<&'w Win>,
+    device_selector: DeviceSelector,
 }
 
 impl<'w> Default for CtxBuilder<'w> {
@@ -70,6 +85,7 @@ impl<'w> Default for CtxBuilder<'w> {
         Self {
             debug: true,
             win: None,
+            device_selector: DeviceSelector::default(),
         }
     }
 }
@@ -84,7 +100,16 @@ impl<'w> CtxBuilder<'w> {
         self
     }
 
+    pub fn device_selector(mut self, device_selector: DeviceSelector) -> Self {
+        self.device_selector = device_selector;
+        self
+    }
+
     pub fn build(self) -> Ctx {
+        self.try_build().expect("Failed to create Vulkan context")
+    }
+
+    pub fn try_build(self) -> Result<Ctx> {
         let mut extensions_names = vec![];
 
         if self.debug {
@@ -107,6 +132,8 @@ impl<'w> CtxBuilder<'w> {
             extensions_names.push(khr::android_surface::NAME.as_ptr());
         }
 
-        Ctx::new(&extensions_names)
+        let mut ctx = Ctx::try_new(&extensions_names)?;
+        ctx.device_selector = self.device_selector;
+        Ok(ctx)
     }
 }
@@ -14,6 +14,8 @@ pub struct DescriptorKeyBuilder {
     pub node: Handle<Node>,
     pub material: Handle<Material>,
     pub camera: Handle<Camera>,
+    pub buffer: vk::Buffer,
+    pub view: vk::ImageView,
 }
 
 impl DescriptorKeyBuilder {
@@ -42,6 +44,16 @@ impl DescriptorKeyBuilder {
         self
     }
 
+    pub fn buffer(mut self, buffer: vk::Buffer) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    pub fn view(mut self, view: vk::ImageView) -> Self {
+        self.view = view;
+        self
+    }
+
     pub fn build(self) -> DescriptorKey {
         DescriptorKey {
             layout: self.layout,
@@ -49,6 +61,8 @@ impl DescriptorKeyBuilder {
             node: self.node,
             material: self.material,
             camera: self.camera,
+            buffer: self.buffer,
+            view: self.view,
         }
     }
 }
@@ -69,6 +83,12 @@ pub struct DescriptorKey {
 
     /// Unique per camera, for view and projection matrices
     pub camera: Handle<Camera>,
+
+    /// Unique per buffer which does not belong to a model, such as the light buffer
+    pub buffer: vk::Buffer,
+
+    /// Unique per image view which does not belong to a model, such as a G-buffer attachment
+    pub view: vk::ImageView,
 }
 
 impl DescriptorKey {
@@ -82,6 +102,10 @@ pub enum DescriptorEntry<'s> {
     Created(&'s [vk::DescriptorSet]),
 }
 
+/// Storage buffers of a descriptor pool, as the limit of the device can be huge.
+/// Each frame binds a few of them for the lights and shadows of each view.
+const STORAGE_BUFFER_COUNT: u32 = 64;
+
 /// Per-frame resource which contains a descriptor pool and a vector
 /// of descriptor sets of each pipeline layout used for rendering.
 pub struct Descriptors {
@@ -101,6 +125,9 @@ impl Descriptors {
         let sampler_pool_size = vk::DescriptorPoolSize::default()
             .descriptor_count(device.properties.limits.max_descriptor_set_sampled_images) // Support 8 materials for 3 pipelines
             .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER);
+        let storage_pool_size = vk::DescriptorPoolSize::default()
+            .descriptor_count(STORAGE_BUFFER_COUNT)
+            .ty(vk::DescriptorType::STORAGE_BUFFER);
         let input_pool_size = vk::DescriptorPoolSize::default()
             .descriptor_count(
                 device
@@ -110,9 +137,15 @@ impl Descriptors {
             )
             .ty(vk::DescriptorType::INPUT_ATTACHMENT);
 
-        let pool_sizes = vec![uniform_pool_size, sampler_pool_size, input_pool_size];
+        let pool_sizes = vec![
+            uniform_pool_size,
+            sampler_pool_size,
+            storage_pool_size,
+            input_pool_size,
+        ];
         let max_sets = device.properties.limits.max_descriptor_set_uniform_buffers
             + device.properties.limits.max_descriptor_set_sampled_images
+            + STORAGE_BUFFER_COUNT
             + device
                 .properties
                 .limits
@@ -133,13 +166,22 @@ impl Descriptors {
     }
 
     pub fn allocate(&self, layouts: &[vk::DescriptorSetLayout]) -> Vec<vk::DescriptorSet> {
+        self.try_allocate(layouts)
+            .expect("Failed to allocate Vulkan descriptor sets")
+    }
+
+    /// Returns `Error::OutOfMemory` when the pool is exhausted
+    pub fn try_allocate(
+        &self,
+        layouts: &[vk::DescriptorSetLayout],
+    ) -> Result<Vec<vk::DescriptorSet>> {
         assert!(!layouts.is_empty());
         let create_info = vk::DescriptorSetAllocateInfo::default()
             .descriptor_pool(self.pool)
             .set_layouts(layouts);
 
-        unsafe { self.device.allocate_descriptor_sets(&create_info) }
-            .expect("Failed to allocate Vulkan descriptor sets")
+        let sets = unsafe { self.device.allocate_descriptor_sets(&create_info) }?;
+        Ok(sets)
     }
 
     #[allow(clippy::map_entry)]
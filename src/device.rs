@@ -2,7 +2,7 @@
 // Author: Antonio Caggiano <info@antoniocaggiano.eu>
 // SPDX-License-Identifier: MIT
 
-use std::{ffi::CStr, sync::Arc};
+use std::sync::Arc;
 
 use ash::{khr, vk};
 
@@ -10,8 +10,14 @@ use crate::*;
 
 pub struct Device {
     pub graphics_queue_index: u32,
+    /// Transfer-only queue family when available, otherwise the graphics one
+    pub transfer_queue_index: u32,
     pub properties: vk::PhysicalDeviceProperties,
+    /// Features enabled on this logical device
+    pub features: vk::PhysicalDeviceFeatures,
     pub physical: vk::PhysicalDevice,
+    /// Needed for querying physical device properties after creation
+    pub instance: ash::Instance,
     pub device: Arc<ash::Device>,
 }
 
@@ -20,13 +26,11 @@ impl Device {
         instance: &ash::Instance,
         physical: vk::PhysicalDevice,
         surface: Option<&Surface>,
-    ) -> u32 {
+    ) -> Result<u32> {
         // Queue information (instance, physical device)
         let queue_properties =
             unsafe { instance.get_physical_device_queue_family_properties(physical) };
 
-        let mut graphics_queue_index = u32::MAX;
-
         for (i, queue) in queue_properties.iter().enumerate() {
             let mut supports_presentation = true;
 
@@ -37,47 +41,92 @@ impl Device {
                         i as u32,
                         surface.surface,
                     )
-                }
-                .expect("Failed to check presentation support for Vulkan physical device");
+                }?;
             }
 
             if queue.queue_flags.contains(vk::QueueFlags::GRAPHICS) && supports_presentation {
-                graphics_queue_index = i as u32;
-                break;
+                return Ok(i as u32);
             }
         }
 
-        assert!(
-            graphics_queue_index != u32::MAX,
-            "Failed to find graphics queue"
-        );
+        Err(Error::Unsupported("graphics queue".to_string()))
+    }
 
-        graphics_queue_index
+    /// Returns a queue family dedicated to transfers, which usually maps to a DMA engine
+    /// able to copy data while the graphics queue is busy rendering
+    fn get_transfer_queue_index(queue_families: &[vk::QueueFamilyProperties]) -> Option<u32> {
+        let supports = |flags: vk::QueueFlags| {
+            queue_families
+                .iter()
+                .position(|queue| (queue.queue_flags & flags) == vk::QueueFlags::TRANSFER)
+        };
+        supports(vk::QueueFlags::TRANSFER | vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE)
+            .or_else(|| supports(vk::QueueFlags::TRANSFER | vk::QueueFlags::GRAPHICS))
+            .map(|index| index as u32)
     }
 
     pub fn new(instance: &ash::Instance, surface: Option<&Surface>) -> Self {
-        // Physical device
-        let physical = {
-            let phydevs = unsafe {
-                instance
-                    .enumerate_physical_devices()
-                    .expect("Failed to enumerate Vulkan physical devices")
-            };
-            phydevs[0]
-        };
-        let properties = unsafe { instance.get_physical_device_properties(physical) };
-        let name = unsafe { CStr::from_ptr(properties.device_name.as_ptr()) };
-        println!("Physical device: {:?}", name);
+        Self::try_new(instance, surface).expect("Failed to create Vulkan device")
+    }
+
+    pub fn try_new(instance: &ash::Instance, surface: Option<&Surface>) -> Result<Self> {
+        Self::try_new_with_selector(instance, surface, &DeviceSelector::default())
+    }
 
-        let graphics_queue_index = Self::get_graphics_queue_index(instance, physical, surface);
+    /// Returns the physical devices which can render and present to `surface`, if any
+    pub fn get_candidates(
+        instance: &ash::Instance,
+        surface: Option<&Surface>,
+    ) -> Result<Vec<PhysicalDeviceInfo>> {
+        let candidates = PhysicalDeviceInfo::enumerate(instance)?
+            .into_iter()
+            .filter(|info| surface.is_none() || info.supports_extension(khr::swapchain::NAME))
+            .filter(|info| Self::get_graphics_queue_index(instance, info.physical, surface).is_ok())
+            .collect();
+        Ok(candidates)
+    }
+
+    /// Creates a logical device on the physical device chosen by `selector`,
+    /// unless it is overridden by the `RAYCA_DEVICE` environment variable.
+    /// The features and extensions required by `selector` are enabled in both cases.
+    pub fn try_new_with_selector(
+        instance: &ash::Instance,
+        surface: Option<&Surface>,
+        selector: &DeviceSelector,
+    ) -> Result<Self> {
+        // Physical device, which the environment can only choose among those meeting the requirements
+        let candidates: Vec<_> = Self::get_candidates(instance, surface)?
+            .into_iter()
+            .filter(|info| selector.meets_requirements(info))
+            .collect();
+        let env_selector = DeviceSelector::from_env();
+        let info = env_selector
+            .as_ref()
+            .unwrap_or(selector)
+            .select(&candidates)
+            .ok_or_else(|| Error::Unsupported("Vulkan physical device".to_string()))?;
+        let physical = info.physical;
+        println!("Physical device: {:?}", info.get_name());
+
+        let graphics_queue_index = Self::get_graphics_queue_index(instance, physical, surface)?;
+
+        let transfer_queue_index =
+            Self::get_transfer_queue_index(&info.queue_families).unwrap_or(graphics_queue_index);
 
         // Logical device (physical device, surface, device required extensions (swapchain), queue information)
-        let queue_infos = vec![
+        let mut queue_infos = vec![
             vk::DeviceQueueCreateInfo::default()
                 .queue_family_index(graphics_queue_index)
                 // Highest priority for a single graphics queue
                 .queue_priorities(&[1.0]),
         ];
+        if transfer_queue_index != graphics_queue_index {
+            queue_infos.push(
+                vk::DeviceQueueCreateInfo::default()
+                    .queue_family_index(transfer_queue_index)
+                    .queue_priorities(&[0.5]),
+            );
+        }
 
         let mut device_extensions = vec![];
 
@@ -88,22 +137,56 @@ impl Device {
             device_extensions.push(khr::swapchain::NAME.as_ptr());
         }
 
+        for extension in selector.get_required_extensions() {
+            device_extensions.push(extension.as_ptr());
+        }
+
+        let mut features = selector.get_required_features();
+        // Enabled whenever available, as samplers decide whether to use it
+        features.sampler_anisotropy |= info.features.sampler_anisotropy;
+
         let device_create_info = vk::DeviceCreateInfo::default()
             .queue_create_infos(&queue_infos)
-            .enabled_extension_names(&device_extensions);
-
-        let device = unsafe { instance.create_device(physical, &device_create_info, None) }
-            .expect("Failed to create Vulkan logical device");
+            .enabled_extension_names(&device_extensions)
+            .enabled_features(&features);
 
-        let properties = unsafe { instance.get_physical_device_properties(physical) };
+        let device = unsafe { instance.create_device(physical, &device_create_info, None) }?;
 
-        Self {
+        Ok(Self {
             graphics_queue_index,
-            properties,
+            transfer_queue_index,
+            properties: info.properties,
+            features,
             physical,
+            instance: instance.clone(),
             device: Arc::new(device),
+        })
+    }
+
+    pub fn get_format_properties(&self, format: vk::Format) -> vk::FormatProperties {
+        unsafe {
+            self.instance
+                .get_physical_device_format_properties(self.physical, format)
         }
     }
+
+    /// Returns whether optimal tiling images of this format can be sampled
+    pub fn supports_sampled_format(&self, format: vk::Format) -> bool {
+        self.get_format_properties(format)
+            .optimal_tiling_features
+            .contains(vk::FormatFeatureFlags::SAMPLED_IMAGE)
+    }
+
+    /// Returns whether optimal tiling images of this format can be blitted with linear filtering,
+    /// which is needed for generating mipmaps on the GPU
+    pub fn supports_linear_blit(&self, format: vk::Format) -> bool {
+        let features = self.get_format_properties(format).optimal_tiling_features;
+        features.contains(
+            vk::FormatFeatureFlags::BLIT_SRC
+                | vk::FormatFeatureFlags::BLIT_DST
+                | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR,
+        )
+    }
 }
 
 impl Drop for Device {
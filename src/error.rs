@@ -0,0 +1,140 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use std::fmt;
+
+use ash::vk;
+
+/// Errors returned by the fallible `try_*` variants of this crate's APIs
+#[derive(Debug)]
+pub enum Error {
+    /// The Vulkan library could not be loaded
+    Loading(ash::LoadingError),
+
+    /// A Vulkan call failed for a reason other than running out of memory
+    Vulkan(vk::Result),
+
+    /// Host, device, or descriptor pool memory has been exhausted
+    OutOfMemory(vk::Result),
+
+    Io(std::io::Error),
+
+    /// An image could not be decoded
+    Image(::image::ImageError),
+
+    /// A KTX2 container could not be parsed
+    Ktx2(::ktx2::ParseError),
+
+    /// A glTF document could not be parsed
+    Gltf(::gltf::Error),
+
+    /// A glTF model could not be loaded by rayca-gltf
+    Model(String),
+
+    /// A JSON file, such as an action map, could not be read or written
+    Json(serde_json::Error),
+
+    /// An image container holds data which can not be used, such as levels of the wrong size
+    InvalidImage(String),
+
+    /// A shader could not be compiled by Slang
+    Slang(String),
+
+    /// Shader bytecode whose size is not a multiple of 4 bytes
+    InvalidSpirv,
+
+    /// A render graph refers to unknown attachments, or uses them in the wrong way
+    InvalidRenderGraph(String),
+
+    /// Something required is not available, such as a physical device or a queue family
+    Unsupported(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Loading(err) => write!(f, "Failed to load Vulkan: {}", err),
+            Self::Vulkan(result) => write!(f, "Vulkan error: {}", result),
+            Self::OutOfMemory(result) => write!(f, "Out of memory: {}", result),
+            Self::Io(err) => write!(f, "IO error: {}", err),
+            Self::Image(err) => write!(f, "Image error: {}", err),
+            Self::Ktx2(err) => write!(f, "KTX2 error: {}", err),
+            Self::Gltf(err) => write!(f, "glTF error: {}", err),
+            Self::Model(err) => write!(f, "Failed to load glTF model: {}", err),
+            Self::Json(err) => write!(f, "JSON error: {}", err),
+            Self::InvalidImage(msg) => write!(f, "Invalid image: {}", msg),
+            Self::Slang(msg) => write!(f, "Slang error: {}", msg),
+            Self::InvalidSpirv => write!(f, "SPIR-V size is not a multiple of 4"),
+            Self::InvalidRenderGraph(msg) => write!(f, "Invalid render graph: {}", msg),
+            Self::Unsupported(msg) => write!(f, "Unsupported: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Loading(err) => Some(err),
+            Self::Vulkan(result) | Self::OutOfMemory(result) => Some(result),
+            Self::Io(err) => Some(err),
+            Self::Image(err) => Some(err),
+            Self::Ktx2(err) => Some(err),
+            Self::Gltf(err) => Some(err),
+            Self::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<vk::Result> for Error {
+    fn from(result: vk::Result) -> Self {
+        match result {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY
+            | vk::Result::ERROR_OUT_OF_DEVICE_MEMORY
+            | vk::Result::ERROR_OUT_OF_POOL_MEMORY
+            | vk::Result::ERROR_FRAGMENTED_POOL => Self::OutOfMemory(result),
+            result => Self::Vulkan(result),
+        }
+    }
+}
+
+impl From<ash::LoadingError> for Error {
+    fn from(err: ash::LoadingError) -> Self {
+        Self::Loading(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<::image::ImageError> for Error {
+    fn from(err: ::image::ImageError) -> Self {
+        Self::Image(err)
+    }
+}
+
+impl From<::ktx2::ParseError> for Error {
+    fn from(err: ::ktx2::ParseError) -> Self {
+        Self::Ktx2(err)
+    }
+}
+
+impl From<::gltf::Error> for Error {
+    fn from(err: ::gltf::Error) -> Self {
+        Self::Gltf(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// Defaults to this crate's error, while still accepting other error types
+/// such as `Result<(), vk::Result>`
+pub type Result<T, E = Error> = std::result::Result<T, E>;
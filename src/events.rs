@@ -2,17 +2,23 @@
 // Author: Antonio Caggiano <info@antoniocaggiano.eu>
 // SPDX-License-Identifier: MIT
 
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::platform::pump_events::EventLoopExtPumpEvents;
 
 #[cfg(target_os = "android")]
 pub use winit::platform::android::activity::AndroidApp;
+pub use winit::{
+    event::MouseButton,
+    keyboard::{KeyCode, ModifiersState},
+};
 
 use crate::*;
 
-#[derive(Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum ButtonState {
     #[default]
     Released,
@@ -53,26 +59,89 @@ impl ButtonState {
             *self = ButtonState::JustReleased
         }
     }
+
+    /// Returns the state furthest in a press among `states`, so pressing a second button
+    /// bound to the same thing while the first is held does not press it again
+    pub fn most_advanced<I: IntoIterator<Item = ButtonState>>(states: I) -> ButtonState {
+        let rank = |state: &ButtonState| match state {
+            ButtonState::Released => 0,
+            ButtonState::JustReleased => 1,
+            ButtonState::JustPressed => 2,
+            ButtonState::Pressed => 3,
+        };
+        states.into_iter().max_by_key(rank).unwrap_or_default()
+    }
+
+    /// Applies an event of the window system, where keys held down repeat their press
+    pub fn set_down(&mut self, down: bool) {
+        if down != self.is_down() {
+            *self = if down {
+                ButtonState::JustPressed
+            } else {
+                ButtonState::JustReleased
+            };
+        }
+    }
+}
+
+/// Updates every state of `buttons`, forgetting those which are released
+pub(crate) fn update_buttons<K>(buttons: &mut HashMap<K, ButtonState>) {
+    buttons.retain(|_, state| {
+        state.update();
+        *state != ButtonState::Released
+    });
 }
 
 #[derive(Default)]
 pub struct Mouse {
     pub position: Vec2,
+    /// Accumulated since the last update
     pub movement: Vec2,
-    pub left: ButtonState,
-    pub right: ButtonState,
+    /// Wheel steps accumulated since the last update, positive when scrolling up or right
+    pub scroll: Vec2,
+    /// Buttons which are not released, or were released since the last update
+    pub buttons: HashMap<MouseButton, ButtonState>,
 }
 
 impl Mouse {
     pub fn update(&mut self) {
         self.movement = Vec2::ZERO;
-        self.left.update();
-        self.right.update();
+        self.scroll = Vec2::ZERO;
+        update_buttons(&mut self.buttons);
+    }
+
+    pub fn get_button(&self, button: MouseButton) -> ButtonState {
+        self.buttons.get(&button).copied().unwrap_or_default()
+    }
+
+    pub fn is_down(&self, button: MouseButton) -> bool {
+        self.get_button(button).is_down()
+    }
+
+    pub fn set_button(&mut self, button: MouseButton, down: bool) {
+        self.buttons.entry(button).or_default().set_down(down);
+    }
+
+    /// Releases every button which is down
+    pub fn release_all(&mut self) {
+        for state in self.buttons.values_mut() {
+            state.set_down(false);
+        }
+    }
+
+    #[deprecated(note = "use `get_button(MouseButton::Left)` instead")]
+    pub fn left(&self) -> ButtonState {
+        self.get_button(MouseButton::Left)
+    }
+
+    #[deprecated(note = "use `get_button(MouseButton::Right)` instead")]
+    pub fn right(&self) -> ButtonState {
+        self.get_button(MouseButton::Right)
     }
 }
 
 #[repr(u32)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AndroidKeyCode {
     Unknown = 0x0,
     Back = 0x4,
@@ -132,27 +201,167 @@ pub struct AndroidInput {
     pub stop: ButtonState,
 }
 
+impl AndroidInput {
+    pub fn update(&mut self) {
+        for button in self.get_buttons_mut() {
+            button.update();
+        }
+    }
+
+    /// Releases every button which is down
+    pub fn release_all(&mut self) {
+        for button in self.get_buttons_mut() {
+            button.set_down(false);
+        }
+    }
+
+    fn get_buttons_mut(&mut self) -> [&mut ButtonState; 13] {
+        [
+            &mut self.back,
+            &mut self.a,
+            &mut self.b,
+            &mut self.x,
+            &mut self.y,
+            &mut self.l1,
+            &mut self.r1,
+            &mut self.l2,
+            &mut self.r2,
+            &mut self.l3,
+            &mut self.r3,
+            &mut self.play,
+            &mut self.stop,
+        ]
+    }
+
+    pub fn get_button(&self, code: AndroidKeyCode) -> ButtonState {
+        match code {
+            AndroidKeyCode::Unknown => ButtonState::Released,
+            AndroidKeyCode::Back => self.back,
+            AndroidKeyCode::A => self.a,
+            AndroidKeyCode::B => self.b,
+            AndroidKeyCode::X => self.x,
+            AndroidKeyCode::Y => self.y,
+            AndroidKeyCode::L1 => self.l1,
+            AndroidKeyCode::R1 => self.r1,
+            AndroidKeyCode::L2 => self.l2,
+            AndroidKeyCode::R2 => self.r2,
+            AndroidKeyCode::L3 => self.l3,
+            AndroidKeyCode::R3 => self.r3,
+            AndroidKeyCode::Play => self.play,
+            AndroidKeyCode::Stop => self.stop,
+        }
+    }
+
+    pub fn get_button_mut(&mut self, code: AndroidKeyCode) -> Option<&mut ButtonState> {
+        match code {
+            AndroidKeyCode::Unknown => None,
+            AndroidKeyCode::Back => Some(&mut self.back),
+            AndroidKeyCode::A => Some(&mut self.a),
+            AndroidKeyCode::B => Some(&mut self.b),
+            AndroidKeyCode::X => Some(&mut self.x),
+            AndroidKeyCode::Y => Some(&mut self.y),
+            AndroidKeyCode::L1 => Some(&mut self.l1),
+            AndroidKeyCode::R1 => Some(&mut self.r1),
+            AndroidKeyCode::L2 => Some(&mut self.l2),
+            AndroidKeyCode::R2 => Some(&mut self.r2),
+            AndroidKeyCode::L3 => Some(&mut self.l3),
+            AndroidKeyCode::R3 => Some(&mut self.r3),
+            AndroidKeyCode::Play => Some(&mut self.play),
+            AndroidKeyCode::Stop => Some(&mut self.stop),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Input {
-    pub q: ButtonState,
-    pub w: ButtonState,
-    pub e: ButtonState,
-    pub a: ButtonState,
-    pub s: ButtonState,
-    pub d: ButtonState,
+    /// Keys by their physical location, which are not released or were released since the last update
+    pub keys: HashMap<KeyCode, ButtonState>,
+    pub modifiers: ModifiersState,
+    /// Text typed since the last update, following the keyboard layout
+    pub text: String,
     pub mouse: Mouse,
+    /// Whether any finger is touching the screen
+    pub touch: ButtonState,
+    pub touches: Touches,
+    /// Connected gamepads by id
+    pub gamepads: BTreeMap<usize, Gamepad>,
+    /// Gamepads connected or disconnected since the last update
+    pub gamepad_events: Vec<GamepadEvent>,
 
     pub android: AndroidInput,
 }
 
 impl Input {
     pub fn update(&mut self) {
+        update_buttons(&mut self.keys);
+        self.text.clear();
         self.mouse.update();
+        self.touch.update();
+        self.touches.update();
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.update();
+        }
+        self.gamepad_events.clear();
+        self.android.update();
+    }
+
+    pub fn get_key(&self, key: KeyCode) -> ButtonState {
+        self.keys.get(&key).copied().unwrap_or_default()
+    }
+
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.get_key(key).is_down()
+    }
+
+    pub fn set_key(&mut self, key: KeyCode, down: bool) {
+        self.keys.entry(key).or_default().set_down(down);
+    }
+
+    /// Releases every key and button which is down, as the window stops receiving
+    /// their events when it loses focus, and they would be stuck down otherwise
+    pub fn release_all(&mut self) {
+        for state in self.keys.values_mut() {
+            state.set_down(false);
+        }
+        self.modifiers = ModifiersState::empty();
+        self.mouse.release_all();
+        self.android.release_all();
+    }
+
+    #[deprecated(note = "use `get_key(KeyCode::KeyQ)` instead")]
+    pub fn q(&self) -> ButtonState {
+        self.get_key(KeyCode::KeyQ)
+    }
+
+    #[deprecated(note = "use `get_key(KeyCode::KeyW)` instead")]
+    pub fn w(&self) -> ButtonState {
+        self.get_key(KeyCode::KeyW)
+    }
+
+    #[deprecated(note = "use `get_key(KeyCode::KeyE)` instead")]
+    pub fn e(&self) -> ButtonState {
+        self.get_key(KeyCode::KeyE)
+    }
+
+    #[deprecated(note = "use `get_key(KeyCode::KeyA)` instead")]
+    pub fn a(&self) -> ButtonState {
+        self.get_key(KeyCode::KeyA)
+    }
+
+    #[deprecated(note = "use `get_key(KeyCode::KeyS)` instead")]
+    pub fn s(&self) -> ButtonState {
+        self.get_key(KeyCode::KeyS)
+    }
+
+    #[deprecated(note = "use `get_key(KeyCode::KeyD)` instead")]
+    pub fn d(&self) -> ButtonState {
+        self.get_key(KeyCode::KeyD)
     }
 }
 
 pub struct Events {
     pub event_loop: EventLoop<()>,
+    gamepads: Option<GamepadBackend>,
 }
 
 impl Events {
@@ -171,12 +380,21 @@ impl Events {
         // Set the control flow to Poll to avoid blocking
         event_loop.set_control_flow(ControlFlow::Poll);
 
-        let mut ret = Self { event_loop };
+        let mut ret = Self {
+            event_loop,
+            #[cfg(not(target_os = "android"))]
+            gamepads: GamepadBackend::new(&mut win.input),
+            #[cfg(target_os = "android")]
+            gamepads: GamepadBackend::new(&win.android_app, &mut win.input),
+        };
         ret.update(win);
         ret
     }
 
     pub fn update(&mut self, win: &mut Win) {
         self.event_loop.pump_app_events(Some(Duration::ZERO), win);
+        if let Some(gamepads) = self.gamepads.as_mut() {
+            gamepads.update(&mut win.input);
+        }
     }
 }
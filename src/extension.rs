@@ -0,0 +1,124 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeMap;
+
+use crate::*;
+
+/// What a node of a glTF document refers to, used to match it with a node of a `Model`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct NodeShape {
+    has_mesh: bool,
+    has_camera: bool,
+    child_count: usize,
+}
+
+/// Parts of a glTF document which rayca-gltf does not keep in its `Model`,
+/// read from the same file the model is loaded from
+#[derive(Clone, Debug, Default)]
+pub struct GltfExtensions {
+    /// The KTX2 image of each `KHR_texture_basisu` texture, by texture index.
+    /// It replaces the `source` of the texture, which becomes the fallback
+    /// for devices which can not sample nor transcode it.
+    pub basisu_sources: BTreeMap<usize, usize>,
+
+    /// Normal scale and occlusion strength of each material, by material index
+    pub material_scales: BTreeMap<usize, MaterialScales>,
+
+    /// `KHR_lights_punctual` lights with the index of the node they are attached to.
+    /// A light referenced by many nodes is repeated for each of them.
+    pub lights: Vec<(usize, PunctualLight)>,
+
+    /// Nodes of the document, in order
+    nodes: Vec<NodeShape>,
+}
+
+impl GltfExtensions {
+    /// Fails for materials sampling textures with a set of texture coordinates
+    /// other than the first, as vertices only have one
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let gltf = ::gltf::Gltf::from_slice(bytes)?;
+        let image_count = gltf.images().len();
+
+        for material in gltf.materials() {
+            let pbr = material.pbr_metallic_roughness();
+            let tex_coords = [
+                pbr.base_color_texture().map(|info| info.tex_coord()),
+                pbr.metallic_roughness_texture()
+                    .map(|info| info.tex_coord()),
+                material.normal_texture().map(|info| info.tex_coord()),
+                material.occlusion_texture().map(|info| info.tex_coord()),
+                material.emissive_texture().map(|info| info.tex_coord()),
+            ];
+            if let Some(set) = tex_coords.into_iter().flatten().find(|&set| set != 0) {
+                return Err(Error::Unsupported(format!(
+                    "Texture coordinate set {} of material {}",
+                    set,
+                    material.index().unwrap_or_default()
+                )));
+            }
+        }
+
+        let basisu_sources = gltf
+            .textures()
+            .filter_map(|texture| {
+                let basisu = texture.extension_value("KHR_texture_basisu")?;
+                let source = basisu.get("source")?.as_u64()? as usize;
+                (source < image_count).then_some((texture.index(), source))
+            })
+            .collect();
+
+        let material_scales = gltf
+            .materials()
+            .filter_map(|material| {
+                let scales = MaterialScales {
+                    normal_scale: material.normal_texture().map_or(1.0, |t| t.scale()),
+                    occlusion_strength: material.occlusion_texture().map_or(1.0, |t| t.strength()),
+                };
+                Some((material.index()?, scales))
+            })
+            .collect();
+
+        let lights = gltf
+            .nodes()
+            .filter_map(|node| Some((node.index(), PunctualLight::from(&node.light()?))))
+            .collect();
+
+        let nodes = gltf
+            .nodes()
+            .map(|node| NodeShape {
+                has_mesh: node.mesh().is_some(),
+                has_camera: node.camera().is_some(),
+                child_count: node.children().len(),
+            })
+            .collect();
+
+        Ok(Self {
+            basisu_sources,
+            material_scales,
+            lights,
+            nodes,
+        })
+    }
+
+    /// Returns the handles of the nodes of `gltf` in the order of the document, or an error when
+    /// they do not match the nodes of the document, as lights would be attached to the wrong ones
+    pub fn get_node_handles(&self, gltf: &Model) -> Result<Vec<Handle<Node>>> {
+        let handles = gltf.nodes.get_handles();
+        let matches = handles.len() == self.nodes.len()
+            && handles.iter().zip(&self.nodes).all(|(&handle, shape)| {
+                gltf.nodes.get(handle).is_some_and(|node| {
+                    node.mesh.is_some() == shape.has_mesh
+                        && node.camera.is_some() == shape.has_camera
+                        && node.children.len() == shape.child_count
+                })
+            });
+        if !matches {
+            return Err(Error::Model(
+                "Nodes do not match the ones of the glTF document".to_string(),
+            ));
+        }
+        Ok(handles)
+    }
+}
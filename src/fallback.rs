@@ -15,22 +15,31 @@ pub struct Fallback {
     pub white_texture: RenderTexture,
     pub white_buffer: RenderBuffer,
     pub white_material: Material,
+    pub pbr_material_buffer: RenderBuffer,
+
+    /// An empty shadow atlas with a single layer, for frames without shadows
+    _shadow_image: RenderImage,
+    _shadow_view: ImageView,
+    pub shadow_sampler: RenderSampler,
+    pub shadow_texture: RenderTexture,
+    /// Holds a single shadow, never sampled as no light refers to it
+    pub shadow_buffer: RenderBuffer,
 
     /// A triangle that covers the whole screen
     pub present_primitive: RenderPrimitive,
 }
 
 impl Fallback {
-    pub fn new(allocator: &Arc<Allocator>, graphics_queue: &GraphicsQueue) -> Self {
+    pub fn new(
+        allocator: &Arc<Allocator>,
+        graphics_queue: &GraphicsQueue,
+        transfer_queue: &TransferQueue,
+    ) -> Self {
+        let mut uploader = Uploader::new_with_queues(allocator, graphics_queue, transfer_queue);
+
         let white = [255, 255, 255, 255];
-        let white_image = RenderImage::from_data(
-            allocator,
-            graphics_queue,
-            &white,
-            1,
-            1,
-            vk::Format::R8G8B8A8_SRGB,
-        );
+        let white_image =
+            RenderImage::from_data(&mut uploader, &white, 1, 1, vk::Format::R8G8B8A8_SRGB);
         let white_view = ImageView::new(&white_image);
         let white_sampler = RenderSampler::new(&allocator.device.device);
         let white_texture = RenderTexture::new(&white_view, &white_sampler);
@@ -38,6 +47,32 @@ impl Fallback {
             RenderBuffer::new::<Color>(allocator, vk::BufferUsageFlags::UNIFORM_BUFFER);
         white_buffer.upload(&Color::WHITE);
         let white_material = Material::default();
+        let mut pbr_material_buffer =
+            RenderBuffer::new::<PbrMaterial>(allocator, vk::BufferUsageFlags::UNIFORM_BUFFER);
+        pbr_material_buffer.upload(&PbrMaterial::new(
+            &white_material,
+            &MaterialScales::default(),
+            false,
+        ));
+
+        let mut shadow_image = RenderImage::try_new_with_layers(
+            allocator,
+            1,
+            1,
+            SHADOW_FORMAT,
+            vk::ImageUsageFlags::SAMPLED,
+            1,
+            1,
+        )
+        .expect("Failed to create Vulkan shadow image");
+        uploader.transition_image(&mut shadow_image, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        let shadow_view =
+            ImageView::new_with_layers(&shadow_image, vk::ImageViewType::TYPE_2D_ARRAY, 0, 1);
+        let shadow_sampler =
+            RenderSampler::new_with_desc(&allocator.device, &SamplerDesc::shadow());
+        let shadow_texture = RenderTexture::new(&shadow_view, &shadow_sampler);
+        let shadow_buffer =
+            RenderBuffer::new::<ShadowData>(allocator, vk::BufferUsageFlags::STORAGE_BUFFER);
 
         // Y pointing down
         let present_vertices = vec![
@@ -47,6 +82,8 @@ impl Fallback {
         ];
         let present_primitive = RenderPrimitive::new(allocator, &present_vertices);
 
+        uploader.submit().wait();
+
         Self {
             _white_image: white_image,
             _white_view: white_view,
@@ -54,6 +91,12 @@ impl Fallback {
             white_texture,
             white_buffer,
             white_material,
+            pbr_material_buffer,
+            _shadow_image: shadow_image,
+            _shadow_view: shadow_view,
+            shadow_sampler,
+            shadow_texture,
+            shadow_buffer,
             present_primitive,
         }
     }
@@ -5,6 +5,7 @@
 use ash::vk;
 use std::{
     collections::{HashMap, hash_map::Entry},
+    path::Path,
     sync::Arc,
 };
 
@@ -16,21 +17,18 @@ pub struct Framebuffer {
     // @todo Make a map of framebuffers indexed by render-pass as key
     pub framebuffer: vk::Framebuffer,
 
-    pub depth_view: ImageView,
-    pub depth_image: RenderImage,
-
-    pub color_view: ImageView,
-    pub color_image: RenderImage,
-
-    pub normal_view: ImageView,
-    pub normal_image: RenderImage,
+    /// Images allocated for the attachments of the render graph which are not external
+    pub attachments: Vec<FramebufferAttachment>,
 
     pub swapchain_view: vk::ImageView,
+    /// Unmanaged image the swapchain view refers to, in the layout left by the render pass
+    pub swapchain_image: RenderImage,
     pub extent: vk::Extent3D,
     device: Arc<ash::Device>,
 }
 
 impl Framebuffer {
+    /// Creates images for the attachments of `pass`, while its external attachment is `image`
     pub fn new(dev: &Dev, image: &RenderImage, pass: &Pass) -> Self {
         // Image view into a swapchain images (device, image, format)
         let swapchain_view = {
@@ -57,62 +55,35 @@ impl Framebuffer {
                 .expect("Failed to create Vulkan image view")
         };
 
-        // Color image with the same settings as the swapchain image
-        let mut color_image = RenderImage::attachment(
-            &dev.allocator,
-            image.extent.width,
-            image.extent.height,
-            image.format,
-        );
-        color_image.transition(
-            &dev.graphics_queue,
-            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
-        );
-
-        let color_view = ImageView::new(&color_image);
-
-        // Depth image
-        let depth_format = vk::Format::D32_SFLOAT;
-        let mut depth_image = RenderImage::attachment(
-            &dev.allocator,
-            image.extent.width,
-            image.extent.height,
-            depth_format,
-        );
-        depth_image.transition(
-            &dev.graphics_queue,
-            vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
-        );
-
-        let depth_view = ImageView::new(&depth_image);
-
-        // Normal image
-        let normal_format = vk::Format::A2R10G10B10_UNORM_PACK32;
-        let mut normal_image = RenderImage::attachment(
-            &dev.allocator,
-            image.extent.width,
-            image.extent.height,
-            normal_format,
-        );
-        normal_image.transition(
-            &dev.graphics_queue,
-            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
-        );
-
-        let normal_view = ImageView::new(&normal_image);
+        let mut uploader = Uploader::new(dev);
+        let attachments: Vec<FramebufferAttachment> = pass
+            .attachments
+            .iter()
+            .filter(|attachment| !attachment.desc.external)
+            .map(|attachment| {
+                FramebufferAttachment::new(dev, &mut uploader, attachment, image.extent)
+            })
+            .collect();
+        uploader.submit().wait();
 
         // Framebuffers (image_views, renderpass)
         let framebuffer = {
-            let attachments = [
-                swapchain_view,
-                depth_view.view,
-                color_view.view,
-                normal_view.view,
-            ];
+            let mut allocated_views = attachments.iter().map(|attachment| attachment.view.view);
+            let views: Vec<vk::ImageView> = pass
+                .attachments
+                .iter()
+                .map(|attachment| {
+                    if attachment.desc.external {
+                        swapchain_view
+                    } else {
+                        allocated_views.next().unwrap()
+                    }
+                })
+                .collect();
 
             let create_info = vk::FramebufferCreateInfo::default()
                 .render_pass(pass.render)
-                .attachments(&attachments)
+                .attachments(&views)
                 .width(image.extent.width)
                 .height(image.extent.height)
                 .layers(1);
@@ -121,19 +92,41 @@ impl Framebuffer {
                 .expect("Failed to create Vulkan framebuffer")
         };
 
+        let mut swapchain_image = RenderImage::unmanaged(
+            &dev.device,
+            image.image,
+            Size2::new(image.extent.width, image.extent.height),
+            image.format,
+            image.color_space,
+        );
+        swapchain_image.layout = pass.present_layout;
+
         Self {
             framebuffer,
-            depth_view,
-            depth_image,
-            color_view,
-            color_image,
-            normal_view,
-            normal_image,
+            attachments,
             swapchain_view,
+            swapchain_image,
             extent: image.extent,
             device: dev.device.device.clone(),
         }
     }
+
+    pub fn get_attachment(&self, name: &str) -> Option<&FramebufferAttachment> {
+        self.attachments
+            .iter()
+            .find(|attachment| attachment.name == name)
+    }
+
+    pub fn get_attachment_mut(&mut self, name: &str) -> Option<&mut FramebufferAttachment> {
+        self.attachments
+            .iter_mut()
+            .find(|attachment| attachment.name == name)
+    }
+
+    /// Returns the view of the attachment called `name`
+    pub fn get_view(&self, name: &str) -> Option<&ImageView> {
+        self.get_attachment(name).map(|attachment| &attachment.view)
+    }
 }
 
 impl Drop for Framebuffer {
@@ -203,12 +196,24 @@ pub struct ViewMatrixKey {
 pub struct ProjMatrixKey {
     pub model: Handle<RenderModel>,
     pub camera: Handle<Camera>,
+    /// Index of the view in `Frame::views`, whose aspect the projection follows
+    pub view: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InverseViewProjKey {
+    pub model: Handle<RenderModel>,
+    pub node: Handle<Node>,
+    /// Index of the view in `Frame::views`, whose aspect the projection follows
+    pub view: usize,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NormalMatrixKey {
     pub model: Handle<RenderModel>,
     pub node: Handle<Node>,
+    /// Camera node whose view space normals are transformed into,
+    /// or `Handle::NONE` for world space normals like the ones of the G-buffer
     pub view: Handle<Node>,
 }
 
@@ -227,14 +232,36 @@ pub struct FrameCache {
     /// Uniform buffers for camera matrices associated to nodes with cameras
     pub view_buffers: BufferCache<ViewMatrixKey>,
 
-    // Uniform buffers for proj matrices associated to cameras
+    // Uniform buffers for proj matrices associated to the cameras of views
     pub proj_buffers: BufferCache<ProjMatrixKey>,
 
+    /// Uniform buffers for the inverse view-projection matrices of the cameras of views,
+    /// used to reconstruct world positions from depth
+    pub inverse_view_proj_buffers: BufferCache<InverseViewProjKey>,
+
     pub material_buffers: BufferCache<MaterialKey>,
 
-    // Buffers for normal matrices associated to mesh nodes and camera nodes
+    /// Uniform buffers with the factors of materials, used by the PBR pipeline
+    pub pbr_material_buffers: BufferCache<MaterialKey>,
+
+    /// Uniform buffers for normal matrices associated to mesh nodes and camera nodes
     pub normal_buffers: BufferCache<NormalMatrixKey>,
 
+    /// Uniform buffer with the ambient light, the exposure, and the number of lights
+    pub lighting_buffer: RenderBuffer,
+
+    /// Storage buffer with the lights gathered this frame, which grows as needed
+    pub light_buffer: RenderBuffer,
+
+    /// Storage buffer with the shadows rendered this frame, which grows as needed
+    pub shadow_buffer: RenderBuffer,
+
+    /// Uniform buffers for the view-projection matrices of shadows, indexed by shadow
+    pub shadow_view_proj_buffers: BufferCache<usize>,
+
+    /// Created by the first frame rendering shadows
+    pub shadow_atlas: Option<ShadowAtlas>,
+
     pub descriptors: Descriptors,
     pub command_buffer: CommandBuffer,
     pub fence: Fence,
@@ -261,8 +288,24 @@ impl FrameCache {
             model_buffers: BufferCache::new(&dev.allocator),
             view_buffers: BufferCache::new(&dev.allocator),
             proj_buffers: BufferCache::new(&dev.allocator),
+            inverse_view_proj_buffers: BufferCache::new(&dev.allocator),
             material_buffers: BufferCache::new(&dev.allocator),
+            pbr_material_buffers: BufferCache::new(&dev.allocator),
             normal_buffers: BufferCache::new(&dev.allocator),
+            lighting_buffer: RenderBuffer::new::<LightingData>(
+                &dev.allocator,
+                vk::BufferUsageFlags::UNIFORM_BUFFER,
+            ),
+            light_buffer: RenderBuffer::new::<LightData>(
+                &dev.allocator,
+                vk::BufferUsageFlags::STORAGE_BUFFER,
+            ),
+            shadow_buffer: RenderBuffer::new::<ShadowData>(
+                &dev.allocator,
+                vk::BufferUsageFlags::STORAGE_BUFFER,
+            ),
+            shadow_view_proj_buffers: BufferCache::new(&dev.allocator),
+            shadow_atlas: None,
             descriptors: Descriptors::new(&dev.device),
             command_buffer,
             fence: Fence::signaled(&dev.device.device),
@@ -280,7 +323,7 @@ impl FrameCache {
     }
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, PartialEq)]
 pub struct CameraDrawInfo {
     pub camera: Handle<Camera>,
     pub node: Handle<Node>,
@@ -297,6 +340,58 @@ impl CameraDrawInfo {
     }
 }
 
+/// A camera rendered into a part of the frame, for split-screen, picture-in-picture, or minimaps.
+/// Offset and extent are relative to the size of the frame.
+#[derive(Clone, Copy)]
+pub struct CameraView {
+    pub camera: CameraDrawInfo,
+    /// Top-left corner, from `[0, 0]` to `[1, 1]`
+    pub offset: [f32; 2],
+    pub extent: [f32; 2],
+}
+
+impl CameraView {
+    /// Creates a view covering the whole frame
+    pub fn new(camera: CameraDrawInfo) -> Self {
+        Self {
+            camera,
+            offset: [0.0, 0.0],
+            extent: [1.0, 1.0],
+        }
+    }
+
+    pub fn offset(mut self, x: f32, y: f32) -> Self {
+        self.offset = [x, y];
+        self
+    }
+
+    pub fn extent(mut self, width: f32, height: f32) -> Self {
+        self.extent = [width, height];
+        self
+    }
+
+    /// Returns the part of a frame of `size` covered by this view, in pixels
+    pub fn get_rect(&self, size: Size2) -> vk::Rect2D {
+        let x = (self.offset[0] * size.width as f32).round() as u32;
+        let y = (self.offset[1] * size.height as f32).round() as u32;
+        let width = (self.extent[0] * size.width as f32).round() as u32;
+        let height = (self.extent[1] * size.height as f32).round() as u32;
+        vk::Rect2D::default()
+            .offset(vk::Offset2D::default().x(x as i32).y(y as i32))
+            .extent(
+                vk::Extent2D::default()
+                    .width(width.min(size.width - x.min(size.width)).max(1))
+                    .height(height.min(size.height - y.min(size.height)).max(1)),
+            )
+    }
+
+    /// Width over height of this view in a frame of `size`
+    pub fn get_aspect(&self, size: Size2) -> f32 {
+        let extent = self.get_rect(size).extent;
+        extent.width as f32 / extent.height as f32
+    }
+}
+
 #[derive(Default, Copy, Clone)]
 pub struct DrawInfo {
     pub primitive: Handle<Primitive>,
@@ -334,6 +429,38 @@ pub struct Frame {
     /// Map of shaders and their associated draw info
     pub shaders_drawinfos: HashMap<u32, Vec<DrawInfo>>,
 
+    /// Lights of the scene in world space, gathered while updating nodes
+    pub lights: Vec<LightData>,
+
+    /// Lights casting shadows, along with their index in `lights`
+    pub shadow_casters: Vec<(usize, PunctualLight)>,
+
+    /// Shadows rendered this frame by `render_shadows`
+    pub shadows: Vec<ShadowData>,
+
+    /// World transforms of camera nodes, needed to fit cascades to their view
+    camera_models: HashMap<ViewMatrixKey, Mat4>,
+
+    /// Clear values of the pass being recorded
+    clear_values: Vec<vk::ClearValue>,
+
+    /// Attachments of the first subpass, cleared again for each view after the first one
+    view_clears: Vec<vk::ClearAttachment>,
+
+    /// Background of the scene being rendered, set by `begin`
+    pub background: Option<Color>,
+
+    /// Cameras rendered by `draw` and `end`, set by `begin`
+    pub views: Vec<CameraView>,
+
+    /// Index of the view being rendered by `draw` and `end`, which pipelines
+    /// use to look up the projections following its aspect
+    pub current_view: usize,
+
+    /// Scale and inversion of the viewports, set by `set_viewport_and_scissor`
+    viewport_scale: f32,
+    invert_viewport: bool,
+
     /// A frame should be able to allocate a uniform buffer on draw
     pub dev: Arc<Dev>,
 }
@@ -357,6 +484,17 @@ impl Frame {
             cache,
             current_transform,
             shaders_drawinfos: HashMap::new(),
+            lights: Vec::new(),
+            shadow_casters: Vec::new(),
+            shadows: Vec::new(),
+            camera_models: HashMap::new(),
+            clear_values: Vec::new(),
+            view_clears: Vec::new(),
+            background: None,
+            views: Vec::new(),
+            current_view: 0,
+            viewport_scale: 1.0,
+            invert_viewport: false,
             dev: dev.clone(),
         }
     }
@@ -365,6 +503,12 @@ impl Frame {
         Size2::new(self.buffer.extent.width, self.buffer.extent.height)
     }
 
+    /// Width over height, which projections follow when the window is resized
+    pub fn get_aspect(&self) -> f32 {
+        let size = self.get_size();
+        size.width.max(1) as f32 / size.height.max(1) as f32
+    }
+
     fn update_node(
         &mut self,
         node_handle: Handle<Node>,
@@ -376,6 +520,14 @@ impl Frame {
         let node = model.get_node(node_handle).unwrap();
         let world_trs = trs * &node.trs;
 
+        if let Some(light_handle) = model.get_node_light(node_handle) {
+            let light = model.get_light(light_handle).unwrap();
+            if light.casts_shadows() {
+                self.shadow_casters.push((self.lights.len(), light.clone()));
+            }
+            self.lights.push(LightData::new(light, &world_trs));
+        }
+
         if node.mesh.is_some() || node.camera.is_some() {
             let model_matrix_key = ModelMatrixKey {
                 model: hmodel,
@@ -387,8 +539,27 @@ impl Frame {
                 .get_or_create::<Mat4>(model_matrix_key);
             uniform_buffer.upload(&world_trs.to_mat4());
 
-            if let Some(camera_handle) = node.camera {
-                let camera = model.get_camera(camera_handle).unwrap();
+            if node.mesh.is_some() {
+                // Inverse-transpose of the model matrix, which keeps normals
+                // perpendicular to their surfaces under non-uniform scale
+                let scale = &world_trs.scale;
+                let normal_trs = Trs::builder()
+                    .rotation(world_trs.rotation)
+                    .scale(Vec3::new(1.0 / scale.x, 1.0 / scale.y, 1.0 / scale.z))
+                    .build();
+                let normal_matrix_key = NormalMatrixKey {
+                    model: hmodel,
+                    node: node_handle,
+                    view: Handle::NONE,
+                };
+                let normal_buffer = self
+                    .cache
+                    .normal_buffers
+                    .get_or_create::<Mat4>(normal_matrix_key);
+                normal_buffer.upload(&normal_trs.to_mat4());
+            }
+
+            if node.camera.is_some() {
                 let view_matrix_key = ViewMatrixKey {
                     model: hmodel,
                     node: node_handle,
@@ -398,16 +569,8 @@ impl Frame {
                     .view_buffers
                     .get_or_create::<Mat4>(view_matrix_key);
                 view_buffer.upload(&world_trs.get_inversed().to_mat4());
-
-                let proj_matrix_key = ProjMatrixKey {
-                    model: hmodel,
-                    camera: camera_handle,
-                };
-                let proj_buffer = self
-                    .cache
-                    .proj_buffers
-                    .get_or_create::<Mat4>(proj_matrix_key);
-                proj_buffer.upload(&camera.projection);
+                self.camera_models
+                    .insert(view_matrix_key, world_trs.to_mat4());
             }
 
             // Collect draw infos for this node
@@ -446,6 +609,14 @@ impl Frame {
                 .material_buffers
                 .get_or_create::<Color>(material_key);
             color_buffer.upload(&material.color);
+
+            let scales = model.get_material_scales(material_handle);
+            let has_normal_texture = model.get_texture(material.normal).is_some();
+            let pbr_material_buffer = self
+                .cache
+                .pbr_material_buffers
+                .get_or_create::<PbrMaterial>(material_key);
+            pbr_material_buffer.upload(&PbrMaterial::new(material, &scales, has_normal_texture));
         }
     }
 
@@ -478,74 +649,328 @@ impl Frame {
         }
     }
 
+    fn update_lights(&mut self, scene: &RenderScene) {
+        let lighting = LightingData::new(scene, self.lights.len() as u32);
+        self.cache.lighting_buffer.upload(&lighting);
+        // Keep the previous buffer when there are no lights, as it can not be empty
+        if !self.lights.is_empty() {
+            self.cache.light_buffer.upload_arr(&self.lights);
+        }
+    }
+
+    /// Uploads the projections of the cameras of the views, following the aspect of each view,
+    /// so that a camera shown by views of different shapes is not stretched in any of them
+    fn update_views(&mut self, scene: &RenderScene) {
+        for (index, view) in self.views.clone().into_iter().enumerate() {
+            let camera_info = view.camera;
+            let view_key = ViewMatrixKey {
+                model: camera_info.model,
+                node: camera_info.node,
+            };
+            let Some(camera_model) = self.camera_models.get(&view_key).copied() else {
+                continue;
+            };
+            let projection = scene
+                .get_model(camera_info.model)
+                .unwrap()
+                .get_projection(camera_info.camera);
+            let aspect = view.get_aspect(self.get_size());
+
+            let proj_matrix_key = ProjMatrixKey {
+                model: camera_info.model,
+                camera: camera_info.camera,
+                view: index,
+            };
+            let proj_buffer = self
+                .cache
+                .proj_buffers
+                .get_or_create::<Mat4>(proj_matrix_key);
+            proj_buffer.upload(&projection.get_matrix(aspect));
+
+            let inverse_view_proj_key = InverseViewProjKey {
+                model: camera_info.model,
+                node: camera_info.node,
+                view: index,
+            };
+            let inverse_view_proj_buffer = self
+                .cache
+                .inverse_view_proj_buffers
+                .get_or_create::<Mat4>(inverse_view_proj_key);
+            inverse_view_proj_buffer
+                .upload(&(camera_model * projection.get_inverse_matrix(aspect)));
+        }
+    }
+
     fn update(&mut self, scene: &RenderScene) {
         self.shaders_drawinfos.clear();
+        self.lights.clear();
+        self.shadow_casters.clear();
+        self.shadows.clear();
+        self.camera_models.clear();
         self.update_scene_nodes(scene);
+        self.update_views(scene);
+        self.update_lights(scene);
         for hmodel in scene.get_models().get_handles() {
             self.update_materials(hmodel, scene);
         }
     }
 
-    /// Updates internal buffers and begins the command buffer
+    /// Updates internal buffers and begins the command buffer, rendering the views of the scene
     pub fn begin(&mut self, scene: &RenderScene) {
+        self.begin_with_views(scene, scene.get_views());
+    }
+
+    /// Like `begin`, but renders `views` instead of the ones of the scene,
+    /// which is useful for rendering other cameras into separate render targets
+    pub fn begin_with_views(&mut self, scene: &RenderScene, views: Vec<CameraView>) {
+        self.views = views;
         self.update(scene);
+        self.background = scene.background;
 
         self.cache
             .command_buffer
             .begin(vk::CommandBufferUsageFlags::default());
     }
 
-    pub fn begin_render(&self, pass: &Pass) {
+    /// Fits the shadows of the lights casting them into the layers of the atlas,
+    /// skipping the ones which do not fit, and updates the lights referring to them
+    fn update_shadows(&mut self, scene: &RenderScene, shadows: &Shadows) {
+        // Cascades follow the camera of the first view
+        let camera_info = self
+            .views
+            .first()
+            .map(|view| view.camera)
+            .unwrap_or_else(|| scene.get_default_camera_draw_info());
+        let view_key = ViewMatrixKey {
+            model: camera_info.model,
+            node: camera_info.node,
+        };
+        let camera_model = self.camera_models.get(&view_key);
+        let camera_projection = scene
+            .get_model(camera_info.model)
+            .map(|model| model.get_projection(camera_info.camera));
+        let camera_aspect = self
+            .views
+            .first()
+            .map(|view| view.get_aspect(self.get_size()))
+            .unwrap_or_else(|| self.get_aspect());
+
+        for (light_index, light) in &self.shadow_casters {
+            let settings = light.shadow.unwrap_or_default();
+            let light_data = &mut self.lights[*light_index];
+            let first_layer = self.shadows.len() as u32;
+            let light_shadows = match light.kind {
+                LightKind::Spot {
+                    outer_cone_angle, ..
+                } => vec![ShadowData::spot(
+                    light_data,
+                    outer_cone_angle,
+                    &settings,
+                    first_layer,
+                    shadows.atlas_size,
+                )],
+                LightKind::Directional => match (camera_model, &camera_projection) {
+                    (Some(camera_model), Some(camera_projection)) => ShadowData::cascades(
+                        light_data,
+                        &settings,
+                        camera_model,
+                        camera_projection,
+                        camera_aspect,
+                        first_layer,
+                        shadows.atlas_size,
+                    ),
+                    // Cascades follow the camera
+                    _ => vec![],
+                },
+                LightKind::Point => vec![],
+            };
+
+            let shadow_count = light_shadows.len() as u32;
+            if shadow_count == 0 || first_layer + shadow_count > shadows.layer_count {
+                continue;
+            }
+            light_data.shadow_index = first_layer as i32;
+            light_data.shadow_count = shadow_count;
+            light_data.shadow_bias = settings.bias;
+            light_data.shadow_normal_bias = settings.normal_bias;
+            self.shadows.extend(light_shadows);
+        }
+
+        if self.shadows.is_empty() {
+            return;
+        }
+        self.cache.light_buffer.upload_arr(&self.lights);
+        self.cache.shadow_buffer.upload_arr(&self.shadows);
+        for (index, shadow) in self.shadows.iter().enumerate() {
+            self.cache
+                .shadow_view_proj_buffers
+                .get_or_create::<Mat4>(index)
+                .upload(&shadow.view_proj);
+        }
+
+        let atlas_matches = self.cache.shadow_atlas.as_ref().is_some_and(|atlas| {
+            atlas.size == shadows.atlas_size && atlas.get_layer_count() == shadows.layer_count
+        });
+        if !atlas_matches {
+            self.cache.shadow_atlas = Some(ShadowAtlas::new(
+                &self.dev,
+                &shadows.pass,
+                shadows.atlas_size,
+                shadows.layer_count,
+            ));
+        }
+    }
+
+    /// Renders the shadow maps of the lights casting shadows into the atlas.
+    /// Needs to be called after `begin` and before `begin_render`.
+    pub fn render_shadows(&mut self, scene: &RenderScene, shadows: &Shadows) {
+        self.update_shadows(scene, shadows);
+        if self.shadows.is_empty() {
+            return;
+        }
+
+        let infos: Vec<DrawInfo> = self.shaders_drawinfos.values().flatten().copied().collect();
+        let pipeline = &shadows.pipeline;
+
+        for (index, shadow) in self.shadows.iter().enumerate() {
+            let atlas = self.cache.shadow_atlas.as_ref().unwrap();
+            let resolution = (shadow.uv_scale * atlas.size as f32).round() as u32;
+            self.cache.command_buffer.begin_depth_render_pass(
+                &shadows.pass,
+                atlas.get_framebuffer(shadow.layer),
+                Size2::new(atlas.size, atlas.size),
+            );
+
+            let viewport = vk::Viewport::default()
+                .width(resolution as f32)
+                .height(resolution as f32)
+                .min_depth(0.0)
+                .max_depth(1.0);
+            self.cache.command_buffer.set_viewport(viewport);
+            let scissor = vk::Rect2D::default()
+                .extent(vk::Extent2D::default().width(resolution).height(resolution));
+            self.cache.command_buffer.set_scissor(scissor);
+
+            pipeline.bind(&self.cache);
+            let view_proj_buffer = self.cache.shadow_view_proj_buffers.get(&index).unwrap();
+            let key = DescriptorKey::builder()
+                .layout(pipeline.get_layout())
+                .buffer(view_proj_buffer.buffer)
+                .build();
+            pipeline.bind_view_proj(
+                &self.cache.command_buffer,
+                &mut self.cache.descriptors,
+                key,
+                view_proj_buffer,
+            );
+
+            for info in infos.iter() {
+                let model = scene.get_model(info.model).unwrap();
+                let model_key = ModelMatrixKey {
+                    model: info.model,
+                    node: info.node,
+                };
+                let key = DescriptorKey::builder()
+                    .layout(pipeline.get_layout())
+                    .model(info.model)
+                    .node(info.node)
+                    .build();
+                pipeline.bind_model(
+                    &self.cache.command_buffer,
+                    &mut self.cache.descriptors,
+                    key,
+                    self.cache.model_buffers.get(&model_key).unwrap(),
+                );
+
+                let render_primitive = model.primitives.get(info.primitive.id.into()).unwrap();
+                pipeline.draw(&self.cache, render_primitive);
+            }
+
+            self.cache.command_buffer.end_render_pass();
+        }
+    }
+
+    /// Begins the render pass, clearing the attachments which need it to the background of the scene
+    pub fn begin_render(&mut self, pass: &Pass) {
         let size = self.get_size();
+        self.clear_values = pass.get_clear_values(self.background);
+        self.view_clears = pass.get_clear_attachments(0, self.background);
         self.cache
             .command_buffer
-            .begin_render_pass(pass, &self.buffer, size);
+            .begin_render_pass(pass, &self.buffer, size, &self.clear_values);
     }
 
+    /// Sets the viewport and scissor to the whole frame, and remembers `scale`
+    /// and `invert_viewport` for the viewports of the views rendered by `draw` and `end`.
     /// - `invert_viewport` according to https://www.saschawillems.de/blog/2019/03/29/flipping-the-vulkan-viewport/
-    pub fn set_viewport_and_scissor(&self, scale: f32, invert_viewport: bool) {
+    pub fn set_viewport_and_scissor(&mut self, scale: f32, invert_viewport: bool) {
+        self.viewport_scale = scale;
+        self.invert_viewport = invert_viewport;
         let size = self.get_size();
+        let rect = vk::Rect2D::default().extent(
+            vk::Extent2D::default()
+                .width(size.width)
+                .height(size.height),
+        );
+        self.set_rect_viewport_and_scissor(rect);
+    }
 
-        let y = if invert_viewport {
-            size.height as f32 * scale
-        } else {
-            0.0
-        };
-        let height = if invert_viewport {
-            -(size.height as f32) * scale
+    /// Restricts rendering to `rect`, in pixels
+    fn set_rect_viewport_and_scissor(&self, rect: vk::Rect2D) {
+        let scale = self.viewport_scale;
+
+        let x = rect.offset.x as f32 * scale;
+        let width = rect.extent.width as f32 * scale;
+        let (y, height) = if self.invert_viewport {
+            let height = rect.extent.height as f32 * scale;
+            (rect.offset.y as f32 * scale + height, -height)
         } else {
-            size.height as f32 * scale
+            (
+                rect.offset.y as f32 * scale,
+                rect.extent.height as f32 * scale,
+            )
         };
 
         let viewport = vk::Viewport::default()
+            .x(x)
             .y(y)
-            .width(size.width as f32 * scale)
+            .width(width)
             .height(height)
-            .min_depth(1.0)
-            .max_depth(0.0);
+            .min_depth(0.0)
+            .max_depth(1.0);
         self.cache.command_buffer.set_viewport(viewport);
-
-        let scissor = vk::Rect2D::default().extent(
-            vk::Extent2D::default()
-                .width(size.width)
-                .height(size.height),
-        );
-        self.cache.command_buffer.set_scissor(scissor);
+        self.cache.command_buffer.set_scissor(rect);
     }
 
+    /// Draws the scene with each view of the frame. Views share the G-buffer, which is
+    /// cleared within each view, so that views drawn later cover the ones they overlap.
     pub fn draw(&mut self, scene: &RenderScene, pipelines: &[Box<dyn RenderPipeline>]) {
-        // Focus on one camera for the moment
-        let camera_infos = vec![scene.get_default_camera_draw_info()];
-
-        for (shader, draw_info) in self.shaders_drawinfos.clone() {
-            let pipeline = &pipelines[shader as usize];
-            pipeline.render(self, scene, &camera_infos, draw_info);
+        for (index, view) in self.views.clone().into_iter().enumerate() {
+            self.current_view = index;
+            let rect = view.get_rect(self.get_size());
+            // The render pass already cleared the attachments for the first view
+            if index > 0 {
+                self.cache
+                    .command_buffer
+                    .clear_attachments(&self.view_clears, rect);
+            }
+            self.set_rect_viewport_and_scissor(rect);
+            let camera_infos = [view.camera];
+            for (shader, draw_info) in self.shaders_drawinfos.clone() {
+                let pipeline = &pipelines[shader as usize];
+                pipeline.render(self, scene, &camera_infos, draw_info);
+            }
         }
     }
 
     pub fn end(&mut self, scene: &RenderScene, pipeline: &dyn RenderPipeline) {
         self.cache.command_buffer.next_subpass();
-        pipeline.render(self, scene, &[], vec![]);
+        // Resolve pipelines may need the camera to reconstruct positions from depth
+        for (index, view) in self.views.clone().into_iter().enumerate() {
+            self.current_view = index;
+            self.set_rect_viewport_and_scissor(view.get_rect(self.get_size()));
+            pipeline.render(self, scene, &[view.camera], vec![]);
+        }
     }
 
     fn end_render_pass_and_command_buffer(&self) {
@@ -553,6 +978,104 @@ impl Frame {
         self.cache.command_buffer.end();
     }
 
+    /// Waits for this frame to be rendered and reads back its present attachment
+    pub fn read_pixels(&mut self) -> ::image::RgbaImage {
+        self.cache.wait();
+        self.buffer
+            .swapchain_image
+            .read_rgba8(&self.dev.allocator, &self.dev.graphics_queue)
+    }
+
+    /// Waits for this frame to be rendered and reads back its color attachment.
+    /// Only render passes which store their attachments, like `Pass::offscreen`, preserve it.
+    pub fn read_color_pixels(&mut self) -> ::image::RgbaImage {
+        self.cache.wait();
+        self.buffer
+            .get_attachment_mut(COLOR_ATTACHMENT)
+            .expect("Render graph has no color attachment")
+            .image
+            .read_rgba8(&self.dev.allocator, &self.dev.graphics_queue)
+    }
+
+    /// Waits for this frame to be rendered and reads back its normal attachment.
+    /// Only render passes which store their attachments, like `Pass::offscreen`, preserve it.
+    pub fn read_normal_pixels(&mut self) -> ::image::Rgba32FImage {
+        self.cache.wait();
+        self.buffer
+            .get_attachment_mut(NORMAL_ATTACHMENT)
+            .expect("Render graph has no normal attachment")
+            .image
+            .read_rgba32f(&self.dev.allocator, &self.dev.graphics_queue)
+    }
+
+    /// Waits for this frame to be rendered and reads back its depth attachment,
+    /// which can be saved as an EXR image without losing precision.
+    /// Only render passes which store their attachments, like `Pass::offscreen`, preserve it.
+    pub fn read_depth_pixels(&mut self) -> ::image::Rgba32FImage {
+        self.cache.wait();
+        self.buffer
+            .get_attachment_mut(DEPTH_ATTACHMENT)
+            .expect("Render graph has no depth attachment")
+            .image
+            .read_rgba32f(&self.dev.allocator, &self.dev.graphics_queue)
+    }
+
+    /// Waits for this frame to be rendered and reads back its material attachment.
+    /// Only render passes which store their attachments, like `Pass::offscreen`, preserve it.
+    pub fn read_material_pixels(&mut self) -> ::image::RgbaImage {
+        self.cache.wait();
+        self.buffer
+            .get_attachment_mut(MATERIAL_ATTACHMENT)
+            .expect("Render graph has no material attachment")
+            .image
+            .read_rgba8(&self.dev.allocator, &self.dev.graphics_queue)
+    }
+
+    /// Waits for this frame to be rendered and reads back its emission attachment.
+    /// Only render passes which store their attachments, like `Pass::offscreen`, preserve it.
+    pub fn read_emission_pixels(&mut self) -> ::image::Rgba32FImage {
+        self.cache.wait();
+        self.buffer
+            .get_attachment_mut(EMISSION_ATTACHMENT)
+            .expect("Render graph has no emission attachment")
+            .image
+            .read_rgba32f(&self.dev.allocator, &self.dev.graphics_queue)
+    }
+
+    /// Reads back the present attachment and writes it to a PNG file at `path`
+    pub fn save_png<P: AsRef<Path>>(&mut self, path: P) -> ::image::ImageResult<()> {
+        self.read_pixels()
+            .save_with_format(path, ::image::ImageFormat::Png)
+    }
+
+    /// Reads back the normal attachment and writes it to an EXR file at `path`, keeping its float values
+    pub fn save_normal_exr<P: AsRef<Path>>(&mut self, path: P) -> ::image::ImageResult<()> {
+        self.read_normal_pixels()
+            .save_with_format(path, ::image::ImageFormat::OpenExr)
+    }
+
+    /// Reads back the emission attachment and writes it to an EXR file at `path`, keeping its float values
+    pub fn save_emission_exr<P: AsRef<Path>>(&mut self, path: P) -> ::image::ImageResult<()> {
+        self.read_emission_pixels()
+            .save_with_format(path, ::image::ImageFormat::OpenExr)
+    }
+
+    /// Reads back the depth attachment and writes it to an EXR file at `path`, keeping its float values
+    pub fn save_depth_exr<P: AsRef<Path>>(&mut self, path: P) -> ::image::ImageResult<()> {
+        self.read_depth_pixels()
+            .save_with_format(path, ::image::ImageFormat::OpenExr)
+    }
+
+    /// Ends the frame and submits its commands without waiting for a swapchain image
+    pub fn submit(&mut self, dev: &Dev) {
+        self.end_render_pass_and_command_buffer();
+
+        let command_buffers = [self.cache.command_buffer.command_buffer];
+        let submits = [vk::SubmitInfo::default().command_buffers(&command_buffers)];
+        dev.graphics_queue
+            .submit(&submits, Some(&mut self.cache.fence));
+    }
+
     pub fn present(
         &mut self,
         dev: &Dev,
@@ -590,21 +1113,82 @@ pub trait Frames {
     fn present(&mut self, dev: &Dev, frame: Frame) -> Result<(), vk::Result>;
 }
 
-/// Offscreen frames work on user allocated images
-struct _OffscreenFrames {
-    _frames: Vec<Frame>,
-    _images: Vec<vk::Image>,
+/// Offscreen frames work on device allocated images,
+/// hence they do not need a surface nor a swapchain
+pub struct OffscreenFrames {
+    /// Frames are dropped before the images they render onto
+    pub frames: Vec<Option<Frame>>,
+    pub images: Vec<RenderImage>,
+    /// Index of the frame returned by the next call to `next_frame`
+    next_id: usize,
+}
+
+impl OffscreenFrames {
+    pub fn new(dev: &Arc<Dev>, size: Size2, pass: &Pass, in_flight_count: usize) -> Self {
+        assert!(
+            in_flight_count > 0,
+            "Offscreen frames need at least one image"
+        );
+
+        let mut frames = Vec::new();
+        let mut images = Vec::new();
+        for id in 0..in_flight_count {
+            // Same format used by the render pass for the present attachment
+            let image = RenderImage::new(
+                &dev.allocator,
+                size.width,
+                size.height,
+                dev.surface_format.format,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC,
+            );
+            let frame = Frame::new(
+                id,
+                in_flight_count,
+                dev,
+                &image,
+                pass,
+                vk::SurfaceTransformFlagsKHR::IDENTITY,
+            );
+            frames.push(Some(frame));
+            images.push(image);
+        }
+
+        Self {
+            frames,
+            images,
+            next_id: 0,
+        }
+    }
+
+    /// Returns the frame with this `id`, if it is not currently in use
+    pub fn get_frame(&self, id: usize) -> Option<&Frame> {
+        self.frames.get(id).and_then(Option::as_ref)
+    }
+
+    /// Returns the frame with this `id`, if it is not currently in use
+    pub fn get_frame_mut(&mut self, id: usize) -> Option<&mut Frame> {
+        self.frames.get_mut(id).and_then(Option::as_mut)
+    }
 }
 
-impl Frames for _OffscreenFrames {
+impl Frames for OffscreenFrames {
     fn next_frame(&mut self) -> Result<Frame, vk::Result> {
-        // Unimplemented
-        Err(vk::Result::ERROR_UNKNOWN)
+        let id = self.next_id;
+        // The frame could still be in use if it has not been presented yet
+        let mut frame = self.frames[id].take().ok_or(vk::Result::NOT_READY)?;
+        assert_eq!(frame.id, id);
+        self.next_id = (id + 1) % self.frames.len();
+        // Wait for this frame's command buffer to be ready
+        frame.cache.wait();
+        Ok(frame)
     }
 
-    fn present(&mut self, _dev: &Dev, _frame: Frame) -> Result<(), vk::Result> {
-        // Unimplemented
-        Err(vk::Result::ERROR_UNKNOWN)
+    fn present(&mut self, dev: &Dev, mut frame: Frame) -> Result<(), vk::Result> {
+        // There is nothing to present, just submit the draw commands
+        frame.submit(dev);
+        let id = frame.id;
+        self.frames[id].replace(frame);
+        Ok(())
     }
 }
 
@@ -0,0 +1,402 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Buttons named after their position, so the bottom face button is `South`
+/// whether it is labelled A or a cross
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    /// Also pressed when the analog trigger is pulled more than halfway
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    /// From -1 on the left to 1 on the right
+    LeftStickX,
+    /// From -1 down to 1 up
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    /// From 0 released to 1 fully pulled
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamepadEvent {
+    Connected(usize),
+    Disconnected(usize),
+}
+
+#[derive(Default)]
+pub struct Gamepad {
+    pub name: String,
+    /// Buttons which are not released, or were released since the last update
+    pub buttons: HashMap<GamepadButton, ButtonState>,
+    pub left_stick: Vec2,
+    pub right_stick: Vec2,
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+}
+
+impl Gamepad {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn update(&mut self) {
+        update_buttons(&mut self.buttons);
+    }
+
+    pub fn get_button(&self, button: GamepadButton) -> ButtonState {
+        self.buttons.get(&button).copied().unwrap_or_default()
+    }
+
+    pub fn is_down(&self, button: GamepadButton) -> bool {
+        self.get_button(button).is_down()
+    }
+
+    pub fn set_button(&mut self, button: GamepadButton, down: bool) {
+        self.buttons.entry(button).or_default().set_down(down);
+    }
+
+    pub fn get_axis(&self, axis: GamepadAxis) -> f32 {
+        match axis {
+            GamepadAxis::LeftStickX => self.left_stick.x,
+            GamepadAxis::LeftStickY => self.left_stick.y,
+            GamepadAxis::RightStickX => self.right_stick.x,
+            GamepadAxis::RightStickY => self.right_stick.y,
+            GamepadAxis::LeftTrigger => self.left_trigger,
+            GamepadAxis::RightTrigger => self.right_trigger,
+        }
+    }
+
+    pub fn set_axis(&mut self, axis: GamepadAxis, value: f32) {
+        match axis {
+            GamepadAxis::LeftStickX => self.left_stick.x = value,
+            GamepadAxis::LeftStickY => self.left_stick.y = value,
+            GamepadAxis::RightStickX => self.right_stick.x = value,
+            GamepadAxis::RightStickY => self.right_stick.y = value,
+            GamepadAxis::LeftTrigger => {
+                self.left_trigger = value;
+                self.set_button(GamepadButton::LeftTrigger, value > 0.5);
+            }
+            GamepadAxis::RightTrigger => {
+                self.right_trigger = value;
+                self.set_button(GamepadButton::RightTrigger, value > 0.5);
+            }
+        }
+    }
+}
+
+impl AndroidKeyCode {
+    pub fn get_gamepad_button(&self) -> Option<GamepadButton> {
+        match self {
+            Self::A => Some(GamepadButton::South),
+            Self::B => Some(GamepadButton::East),
+            Self::X => Some(GamepadButton::West),
+            Self::Y => Some(GamepadButton::North),
+            Self::L1 => Some(GamepadButton::LeftBumper),
+            Self::R1 => Some(GamepadButton::RightBumper),
+            Self::L2 => Some(GamepadButton::LeftTrigger),
+            Self::R2 => Some(GamepadButton::RightTrigger),
+            Self::L3 => Some(GamepadButton::LeftThumb),
+            Self::R3 => Some(GamepadButton::RightThumb),
+            Self::Back => Some(GamepadButton::Select),
+            Self::Play => Some(GamepadButton::Start),
+            Self::Unknown | Self::Stop => None,
+        }
+    }
+}
+
+impl Input {
+    /// Returns the gamepad with this id, connecting it when new
+    pub fn get_gamepad_mut<S: Into<String>>(&mut self, id: usize, name: S) -> &mut Gamepad {
+        self.gamepads.entry(id).or_insert_with(|| {
+            self.gamepad_events.push(GamepadEvent::Connected(id));
+            Gamepad::new(name)
+        })
+    }
+
+    /// Returns the gamepad with the lowest id, which receives the buttons and sticks of Android
+    /// gamepads, as window events do not tell which device they come from
+    pub fn get_first_gamepad_mut(&mut self) -> Option<&mut Gamepad> {
+        self.gamepads.values_mut().next()
+    }
+
+    /// Applies the X and Y axes of an Android joystick, which go from -1 to 1
+    /// growing rightwards and downwards, to the left stick of the first gamepad
+    pub fn set_android_joystick(&mut self, axes: Vec2) {
+        self.android.left_axis = axes;
+        if let Some(gamepad) = self.get_first_gamepad_mut() {
+            gamepad.set_axis(GamepadAxis::LeftStickX, axes.x);
+            gamepad.set_axis(GamepadAxis::LeftStickY, -axes.y);
+        }
+    }
+
+    pub fn remove_gamepad(&mut self, id: usize) {
+        if self.gamepads.remove(&id).is_some() {
+            self.gamepad_events.push(GamepadEvent::Disconnected(id));
+        }
+    }
+
+    /// Returns the most advanced state of a button among all gamepads
+    pub fn get_gamepad_button(&self, button: GamepadButton) -> ButtonState {
+        ButtonState::most_advanced(
+            self.gamepads
+                .values()
+                .map(|gamepad| gamepad.get_button(button)),
+        )
+    }
+
+    /// Returns the value of an axis farthest from rest among all gamepads
+    pub fn get_gamepad_axis(&self, axis: GamepadAxis) -> f32 {
+        self.gamepads
+            .values()
+            .map(|gamepad| gamepad.get_axis(axis))
+            .fold(0.0, |a, b| if b.abs() > a.abs() { b } else { a })
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+mod backend {
+    use super::*;
+
+    impl GamepadButton {
+        fn from_gilrs(button: gilrs::Button) -> Option<Self> {
+            use gilrs::Button;
+            match button {
+                Button::South => Some(Self::South),
+                Button::East => Some(Self::East),
+                Button::North => Some(Self::North),
+                Button::West => Some(Self::West),
+                Button::LeftTrigger => Some(Self::LeftBumper),
+                Button::RightTrigger => Some(Self::RightBumper),
+                Button::LeftTrigger2 => Some(Self::LeftTrigger),
+                Button::RightTrigger2 => Some(Self::RightTrigger),
+                Button::Select => Some(Self::Select),
+                Button::Start => Some(Self::Start),
+                Button::Mode => Some(Self::Mode),
+                Button::LeftThumb => Some(Self::LeftThumb),
+                Button::RightThumb => Some(Self::RightThumb),
+                Button::DPadUp => Some(Self::DPadUp),
+                Button::DPadDown => Some(Self::DPadDown),
+                Button::DPadLeft => Some(Self::DPadLeft),
+                Button::DPadRight => Some(Self::DPadRight),
+                _ => None,
+            }
+        }
+    }
+
+    impl GamepadAxis {
+        fn from_gilrs(axis: gilrs::Axis) -> Option<Self> {
+            use gilrs::Axis;
+            match axis {
+                Axis::LeftStickX => Some(Self::LeftStickX),
+                Axis::LeftStickY => Some(Self::LeftStickY),
+                Axis::RightStickX => Some(Self::RightStickX),
+                Axis::RightStickY => Some(Self::RightStickY),
+                _ => None,
+            }
+        }
+    }
+
+    /// Reads gamepads through evdev on Linux, and the native APIs of other desktop platforms
+    pub struct GamepadBackend {
+        gilrs: gilrs::Gilrs,
+    }
+
+    impl GamepadBackend {
+        /// Returns `None` when gamepads are not supported on this system
+        pub fn new(input: &mut Input) -> Option<Self> {
+            let gilrs = gilrs::Gilrs::new()
+                .inspect_err(|err| eprintln!("Gamepads are not available: {}", err))
+                .ok()?;
+            // Gamepads connected before start do not generate any event
+            for (id, gamepad) in gilrs.gamepads() {
+                input.get_gamepad_mut(id.into(), gamepad.name());
+            }
+            Some(Self { gilrs })
+        }
+
+        pub fn update(&mut self, input: &mut Input) {
+            while let Some(gilrs::Event { id, event, .. }) = self.gilrs.next_event() {
+                let gamepad = self.gilrs.gamepad(id);
+                let name = gamepad.name();
+                let id = usize::from(id);
+                match event {
+                    gilrs::EventType::Connected => {
+                        input.get_gamepad_mut(id, name);
+                    }
+                    gilrs::EventType::Disconnected => input.remove_gamepad(id),
+                    gilrs::EventType::ButtonChanged(button, value, _) => {
+                        let gamepad = input.get_gamepad_mut(id, name);
+                        match button {
+                            // Analog triggers report how much they are pulled
+                            gilrs::Button::LeftTrigger2 => {
+                                gamepad.set_axis(GamepadAxis::LeftTrigger, value)
+                            }
+                            gilrs::Button::RightTrigger2 => {
+                                gamepad.set_axis(GamepadAxis::RightTrigger, value)
+                            }
+                            button => {
+                                if let Some(button) = GamepadButton::from_gilrs(button) {
+                                    gamepad.set_button(button, value > 0.5);
+                                }
+                            }
+                        }
+                    }
+                    gilrs::EventType::AxisChanged(axis, value, _) => {
+                        if let Some(axis) = GamepadAxis::from_gilrs(axis) {
+                            input.get_gamepad_mut(id, name).set_axis(axis, value);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "android")]
+mod backend {
+    use std::time::{Duration, Instant};
+
+    use jni::JavaVM;
+    use jni::objects::{JIntArray, JString, JValue};
+
+    use super::*;
+
+    /// Input sources of `android.view.InputDevice`
+    const SOURCE_GAMEPAD: i32 = 0x0000_0401;
+    const SOURCE_JOYSTICK: i32 = 0x0100_0010;
+
+    /// How often the input devices are listed for finding gamepads connecting or disconnecting
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    /// Finds the gamepads connected to the device through `android.view.InputDevice`,
+    /// as the native activity does not receive events when they connect or disconnect.
+    /// Their buttons and sticks arrive as window events.
+    pub struct GamepadBackend {
+        vm: JavaVM,
+        last_poll: Option<Instant>,
+    }
+
+    impl GamepadBackend {
+        /// Returns `None` when the Java VM of the app is not available
+        pub fn new(android_app: &AndroidApp, input: &mut Input) -> Option<Self> {
+            let vm = unsafe { JavaVM::from_raw(android_app.vm_as_ptr().cast()) }
+                .inspect_err(|err| eprintln!("Gamepads are not available: {}", err))
+                .ok()?;
+            let mut ret = Self {
+                vm,
+                last_poll: None,
+            };
+            ret.update(input);
+            Some(ret)
+        }
+
+        pub fn update(&mut self, input: &mut Input) {
+            if self
+                .last_poll
+                .is_some_and(|last_poll| last_poll.elapsed() < POLL_INTERVAL)
+            {
+                return;
+            }
+            self.last_poll = Some(Instant::now());
+
+            let connected = match self.get_gamepads() {
+                Ok(connected) => connected,
+                Err(err) => {
+                    eprintln!("Failed to list gamepads: {}", err);
+                    return;
+                }
+            };
+            let disconnected: Vec<usize> = input
+                .gamepads
+                .keys()
+                .copied()
+                .filter(|id| !connected.iter().any(|(connected, _)| connected == id))
+                .collect();
+            for id in disconnected {
+                input.remove_gamepad(id);
+            }
+            for (id, name) in connected {
+                input.get_gamepad_mut(id, name);
+            }
+        }
+
+        /// Returns the ids and names of the input devices which are gamepads or joysticks
+        fn get_gamepads(&self) -> jni::errors::Result<Vec<(usize, String)>> {
+            const CLASS: &str = "android/view/InputDevice";
+            let mut env = self.vm.attach_current_thread_permanently()?;
+            // The thread never returns to Java, so local references need to be freed here
+            env.with_local_frame(16, |env| {
+                let ids: JIntArray = env
+                    .call_static_method(CLASS, "getDeviceIds", "()[I", &[])?
+                    .l()?
+                    .into();
+                let mut buffer = vec![0; env.get_array_length(&ids)? as usize];
+                env.get_int_array_region(&ids, 0, &mut buffer)?;
+
+                let mut ret = vec![];
+                for id in buffer {
+                    let device = env
+                        .call_static_method(
+                            CLASS,
+                            "getDevice",
+                            "(I)Landroid/view/InputDevice;",
+                            &[JValue::Int(id)],
+                        )?
+                        .l()?;
+                    // Devices may disconnect while listing them
+                    if device.is_null() {
+                        continue;
+                    }
+                    let sources = env.call_method(&device, "getSources", "()I", &[])?.i()?;
+                    if sources & SOURCE_GAMEPAD == SOURCE_GAMEPAD
+                        || sources & SOURCE_JOYSTICK == SOURCE_JOYSTICK
+                    {
+                        let name: JString = env
+                            .call_method(&device, "getName", "()Ljava/lang/String;", &[])?
+                            .l()?
+                            .into();
+                        let string: String = env.get_string(&name)?.into();
+                        env.delete_local_ref(name)?;
+                        ret.push((id as usize, string));
+                    }
+                    env.delete_local_ref(device)?;
+                }
+                Ok(ret)
+            })
+        }
+    }
+}
+
+pub use backend::GamepadBackend;
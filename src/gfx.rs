@@ -11,6 +11,10 @@ pub struct Vkr {
     pub present_pipeline: PipelinePresent,
     pub normal_pipeline: PipelineNormal,
     pub depth_pipeline: PipelineDepth,
+    pub lighting_pipeline: PipelineLighting,
+    /// Its atlas size and layer count can be changed at any time,
+    /// as frames recreate their shadow atlas when they do not match
+    pub shadows: Shadows,
     pub frames: SwapchainFrames,
     pub pass: Pass,
     pub dev: Arc<Dev>,
@@ -60,6 +64,18 @@ impl Vkr {
             &win.android_app,
             &pass,
         );
+        let lighting_pipeline = PipelineLighting::new::<PresentVertex>(
+            #[cfg(target_os = "android")]
+            &win.android_app,
+            &pass,
+        );
+        let shadows = Shadows::new(
+            #[cfg(target_os = "android")]
+            &win.android_app,
+            &dev,
+            Shadows::DEFAULT_ATLAS_SIZE,
+            Shadows::DEFAULT_LAYER_COUNT,
+        );
 
         Self {
             events: Some(events),
@@ -73,6 +89,8 @@ impl Vkr {
             present_pipeline,
             normal_pipeline,
             depth_pipeline,
+            lighting_pipeline,
+            shadows,
         }
     }
 
@@ -140,6 +158,9 @@ pub struct Dev {
     pub fallback: Fallback,
     pub surface_format: vk::SurfaceFormatKHR,
     pub graphics_queue: GraphicsQueue,
+    pub transfer_queue: TransferQueue,
+    /// Samplers shared by all the models created with this device
+    pub sampler_cache: SamplerCache,
     /// Needs to be public if we want to create buffers outside this module.
     /// The allocator is shared between the various buffers to release resources on drop.
     pub allocator: Arc<Allocator>,
@@ -148,8 +169,17 @@ pub struct Dev {
 
 impl Dev {
     pub fn new(ctx: &Ctx, surface: Option<&Surface>) -> Self {
-        let device = Arc::new(Device::new(&ctx.instance, surface));
+        Self::try_new(ctx, surface).expect("Failed to create Vulkan device")
+    }
+
+    pub fn try_new(ctx: &Ctx, surface: Option<&Surface>) -> Result<Self> {
+        let device = Arc::new(Device::try_new_with_selector(
+            &ctx.instance,
+            surface,
+            &ctx.device_selector,
+        )?);
         let graphics_queue = GraphicsQueue::new(&device);
+        let transfer_queue = TransferQueue::new(&device);
 
         // Surface format
         let mut surface_format = vk::SurfaceFormatKHR::default()
@@ -162,24 +192,26 @@ impl Dev {
                     surface
                         .ext
                         .get_physical_device_surface_formats(device.physical, surface.surface)
-                }
-                .expect("Failed to get Vulkan physical device surface formats");
+                }?;
 
                 surface_formats[1]
             }
         }
         println!("Surface format: {:?}", surface_format.format);
 
-        let allocator = Arc::new(Allocator::new(ctx, &device));
-        let fallback = Fallback::new(&allocator, &graphics_queue);
+        let allocator = Arc::new(Allocator::try_new(ctx, &device)?);
+        let fallback = Fallback::new(&allocator, &graphics_queue, &transfer_queue);
+        let sampler_cache = SamplerCache::new(&device);
 
-        Self {
+        Ok(Self {
             fallback,
             surface_format,
             graphics_queue,
+            transfer_queue,
+            sampler_cache,
             allocator,
             device,
-        }
+        })
     }
 
     pub fn wait(&self) {
@@ -204,16 +236,19 @@ pub struct Allocator {
 
 impl Allocator {
     pub fn new(ctx: &Ctx, device: &Arc<Device>) -> Self {
+        Self::try_new(ctx, device).expect("Failed to create Vulkan allocator")
+    }
+
+    pub fn try_new(ctx: &Ctx, device: &Arc<Device>) -> Result<Self> {
         let allocator = {
             let create_info =
                 vk_mem::AllocatorCreateInfo::new(&ctx.instance, &device, device.physical);
             unsafe { vk_mem::Allocator::new(create_info) }
-        }
-        .expect("Failed to create Vulkan allocator");
-        Self {
+        }?;
+        Ok(Self {
             allocator,
             device: device.clone(),
-        }
+        })
     }
 }
 
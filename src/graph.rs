@@ -0,0 +1,701 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use crate::*;
+
+/// Attachment of the deferred graph where the resolve subpass writes, the swapchain image
+pub const PRESENT_ATTACHMENT: &str = "present";
+/// Depth attachment of the deferred graph
+pub const DEPTH_ATTACHMENT: &str = "depth";
+/// Color attachment of the G-buffer written by the geometry subpass
+pub const COLOR_ATTACHMENT: &str = "color";
+/// Normal attachment of the G-buffer written by the geometry subpass
+pub const NORMAL_ATTACHMENT: &str = "normal";
+/// Attachment of the G-buffer with metalness in red and ambient occlusion in green
+pub const MATERIAL_ATTACHMENT: &str = "material";
+/// Attachment of the G-buffer with the emitted radiance, which is not bound to `[0, 1]`
+pub const EMISSION_ATTACHMENT: &str = "emission";
+
+// @todo Multisampled attachments, once rayca-pipe creates pipelines with the sample count of their subpass
+/// An image used by the passes of a render graph
+#[derive(Clone)]
+pub struct AttachmentDesc {
+    pub name: String,
+    pub format: vk::Format,
+    /// Value the attachment is cleared to before its first use, `None` means its contents
+    /// at the beginning of the render pass do not matter
+    pub clear: Option<vk::ClearValue>,
+    /// Whether the clear value is replaced by the background of the scene, when it has one
+    pub background: bool,
+    /// Whether its contents from before the render pass are kept instead of being cleared,
+    /// in which case the attachment is expected to be in its final layout
+    pub load: bool,
+    /// Whether its contents are needed after the render pass, for presenting or reading back.
+    /// Attachments which are not stored are transient, and may never leave tile memory.
+    pub store: bool,
+    /// Whether the image is provided by the user of the graph, such as a swapchain image,
+    /// instead of being allocated by the framebuffer
+    pub external: bool,
+    /// Layout of the attachment at the end of the render pass,
+    /// `None` leaves it in the layout used for writing it
+    pub final_layout: Option<vk::ImageLayout>,
+}
+
+impl AttachmentDesc {
+    pub fn new(name: impl Into<String>, format: vk::Format) -> Self {
+        Self {
+            name: name.into(),
+            format,
+            clear: None,
+            background: false,
+            load: false,
+            store: false,
+            external: false,
+            final_layout: None,
+        }
+    }
+
+    /// Creates a color attachment cleared to `color`
+    pub fn color(name: impl Into<String>, format: vk::Format, color: [f32; 4]) -> Self {
+        let mut clear = vk::ClearValue::default();
+        clear.color.float32 = color;
+        Self::new(name, format).clear(clear)
+    }
+
+    /// Creates a depth attachment cleared to `depth`
+    pub fn depth(name: impl Into<String>, format: vk::Format, depth: f32) -> Self {
+        let mut clear = vk::ClearValue::default();
+        clear.depth_stencil.depth = depth;
+        Self::new(name, format).clear(clear)
+    }
+
+    pub fn clear(mut self, clear: vk::ClearValue) -> Self {
+        self.clear = Some(clear);
+        self
+    }
+
+    /// Leaves the contents at the beginning of the render pass undefined
+    pub fn dont_care(mut self) -> Self {
+        self.clear = None;
+        self.load = false;
+        self
+    }
+
+    /// Clears a color attachment to the background of the scene instead, when it has one
+    pub fn background(mut self) -> Self {
+        self.background = true;
+        self
+    }
+
+    /// Keeps the contents of the attachment from before the render pass
+    pub fn load(mut self) -> Self {
+        self.load = true;
+        self
+    }
+
+    pub fn store(mut self, store: bool) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Marks the attachment as provided by the user of the graph, which implies storing it
+    pub fn external(mut self) -> Self {
+        self.external = true;
+        self.store = true;
+        self
+    }
+
+    pub fn final_layout(mut self, final_layout: vk::ImageLayout) -> Self {
+        self.final_layout = Some(final_layout);
+        self
+    }
+
+    pub fn is_depth(&self) -> bool {
+        RenderImage::is_depth_format(self.format)
+    }
+
+    /// Returns how the attachment is loaded at the beginning of the render pass
+    pub fn get_load_op(&self) -> vk::AttachmentLoadOp {
+        if self.load {
+            vk::AttachmentLoadOp::LOAD
+        } else if self.clear.is_some() {
+            vk::AttachmentLoadOp::CLEAR
+        } else {
+            vk::AttachmentLoadOp::DONT_CARE
+        }
+    }
+
+    /// Returns the value the attachment is cleared to, which for attachments
+    /// cleared to the background is `background`, if any
+    pub fn get_clear_value(&self, background: Option<Color>) -> vk::ClearValue {
+        match background {
+            Some(color) if self.background => {
+                let mut clear = vk::ClearValue::default();
+                clear.color.float32 = [color.r, color.g, color.b, color.a];
+                clear
+            }
+            _ => self.clear.unwrap_or_default(),
+        }
+    }
+
+    /// Layout used while writing the attachment
+    fn get_write_layout(&self) -> vk::ImageLayout {
+        if self.is_depth() {
+            vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL
+        } else {
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL
+        }
+    }
+}
+
+/// A pass of a render graph, which becomes a subpass of the compiled render pass.
+/// Attachments are referred to by name.
+#[derive(Clone, Debug, Default)]
+pub struct PassDesc {
+    pub name: String,
+    /// Written as color attachments, in the order of the outputs of fragment shaders
+    pub colors: Vec<String>,
+    /// Tested and written as the depth attachment
+    pub depth: Option<String>,
+    /// Read as input attachments, in the order of their input attachment indices
+    pub inputs: Vec<String>,
+}
+
+impl PassDesc {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn color(mut self, attachment: impl Into<String>) -> Self {
+        self.colors.push(attachment.into());
+        self
+    }
+
+    pub fn depth(mut self, attachment: impl Into<String>) -> Self {
+        self.depth = Some(attachment.into());
+        self
+    }
+
+    pub fn input(mut self, attachment: impl Into<String>) -> Self {
+        self.inputs.push(attachment.into());
+        self
+    }
+
+    fn writes(&self, attachment: &str) -> bool {
+        self.colors.iter().any(|color| color == attachment)
+            || self.depth.as_deref() == Some(attachment)
+    }
+
+    fn reads(&self, attachment: &str) -> bool {
+        self.inputs.iter().any(|input| input == attachment)
+    }
+
+    fn uses(&self, attachment: &str) -> bool {
+        self.writes(attachment) || self.reads(attachment)
+    }
+}
+
+/// An attachment of a compiled render graph, with everything
+/// needed for creating its image and beginning the render pass
+#[derive(Clone)]
+pub struct PassAttachment {
+    pub desc: AttachmentDesc,
+    /// Usage of images created for this attachment
+    pub usage: vk::ImageUsageFlags,
+    pub final_layout: vk::ImageLayout,
+}
+
+impl PassAttachment {
+    /// Whether the attachment is only needed during the render pass
+    pub fn is_transient(&self) -> bool {
+        !self.desc.store && !self.desc.external
+    }
+}
+
+/// A declarative description of a render pass. Passes are registered in order with the
+/// attachments they read and write, and compiling the graph works out layouts, load and store
+/// operations, dependencies between passes, and which attachments can be transient.
+#[derive(Clone, Default)]
+pub struct RenderGraph {
+    pub attachments: Vec<AttachmentDesc>,
+    pub passes: Vec<PassDesc>,
+}
+
+/// Pipeline stages and accesses of an attachment by a pass
+#[derive(Clone, Copy, Default)]
+struct Access {
+    stages: vk::PipelineStageFlags,
+    access: vk::AccessFlags,
+}
+
+impl std::ops::BitOrAssign for Access {
+    fn bitor_assign(&mut self, other: Self) {
+        self.stages |= other.stages;
+        self.access |= other.access;
+    }
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The graph of `Pass::new` and `Pass::offscreen`. A geometry pass writes the G-buffer,
+    /// which is read by a resolve pass writing the present attachment.
+    /// Other attachments are stored only when `store` is true.
+    pub fn deferred(
+        present_format: vk::Format,
+        present_layout: vk::ImageLayout,
+        store: bool,
+    ) -> Self {
+        Self::new()
+            .attachment(
+                AttachmentDesc::color(
+                    PRESENT_ATTACHMENT,
+                    present_format,
+                    [0.0, 10.0 / 255.0, 28.0 / 255.0, 1.0],
+                )
+                .background()
+                .external()
+                .final_layout(present_layout),
+            )
+            .attachment(
+                AttachmentDesc::depth(DEPTH_ATTACHMENT, vk::Format::D32_SFLOAT, 0.0).store(store),
+            )
+            .attachment(
+                // Pixels without geometry keep this color through the resolve pass
+                AttachmentDesc::color(COLOR_ATTACHMENT, present_format, [0.2, 0.0, 0.1, 1.0])
+                    .background()
+                    .store(store),
+            )
+            .attachment(
+                AttachmentDesc::color(
+                    NORMAL_ATTACHMENT,
+                    vk::Format::A2R10G10B10_UNORM_PACK32,
+                    [0.0, 0.0, 0.0, 1.0],
+                )
+                .store(store),
+            )
+            .attachment(
+                // Dielectric and not occluded
+                AttachmentDesc::color(
+                    MATERIAL_ATTACHMENT,
+                    vk::Format::R8G8B8A8_UNORM,
+                    [0.0, 1.0, 0.0, 0.0],
+                )
+                .store(store),
+            )
+            .attachment(
+                AttachmentDesc::color(
+                    EMISSION_ATTACHMENT,
+                    vk::Format::R16G16B16A16_SFLOAT,
+                    [0.0, 0.0, 0.0, 0.0],
+                )
+                .store(store),
+            )
+            .pass(
+                PassDesc::new("geometry")
+                    .color(COLOR_ATTACHMENT)
+                    .color(NORMAL_ATTACHMENT)
+                    .color(MATERIAL_ATTACHMENT)
+                    .color(EMISSION_ATTACHMENT)
+                    .depth(DEPTH_ATTACHMENT),
+            )
+            .pass(
+                PassDesc::new("resolve")
+                    .color(PRESENT_ATTACHMENT)
+                    .input(COLOR_ATTACHMENT)
+                    .input(NORMAL_ATTACHMENT)
+                    .input(DEPTH_ATTACHMENT)
+                    .input(MATERIAL_ATTACHMENT)
+                    .input(EMISSION_ATTACHMENT),
+            )
+    }
+
+    pub fn attachment(mut self, attachment: AttachmentDesc) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    pub fn pass(mut self, pass: PassDesc) -> Self {
+        self.passes.push(pass);
+        self
+    }
+
+    pub fn get_attachment_index(&self, name: &str) -> Option<usize> {
+        self.attachments
+            .iter()
+            .position(|attachment| attachment.name == name)
+    }
+
+    /// Returns the index of the subpass compiled from the pass called `name`
+    pub fn get_pass_index(&self, name: &str) -> Option<u32> {
+        self.passes
+            .iter()
+            .position(|pass| pass.name == name)
+            .map(|index| index as u32)
+    }
+
+    /// Checks that passes only refer to attachments of the graph, with the right formats
+    fn validate(&self) -> Result<()> {
+        if self.passes.is_empty() {
+            return Err(Error::InvalidRenderGraph("no passes".to_string()));
+        }
+        for pass in &self.passes {
+            let references = pass.colors.iter().chain(&pass.depth).chain(&pass.inputs);
+            for name in references {
+                if self.get_attachment_index(name).is_none() {
+                    return Err(Error::InvalidRenderGraph(format!(
+                        "pass {} refers to unknown attachment {}",
+                        pass.name, name
+                    )));
+                }
+            }
+            let wrong_color = pass.colors.iter().find(|color| {
+                let index = self.get_attachment_index(color).unwrap();
+                self.attachments[index].is_depth()
+            });
+            if let Some(color) = wrong_color {
+                return Err(Error::InvalidRenderGraph(format!(
+                    "pass {} writes depth attachment {} as a color attachment",
+                    pass.name, color
+                )));
+            }
+            if let Some(depth) = &pass.depth {
+                let index = self.get_attachment_index(depth).unwrap();
+                if !self.attachments[index].is_depth() {
+                    return Err(Error::InvalidRenderGraph(format!(
+                        "pass {} uses color attachment {} as its depth attachment",
+                        pass.name, depth
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Layout of an attachment while used by a pass
+    fn get_layout(&self, attachment: &AttachmentDesc, pass: &PassDesc) -> vk::ImageLayout {
+        match (pass.writes(&attachment.name), pass.reads(&attachment.name)) {
+            // Feedback loops need a layout good for everything
+            (true, true) => vk::ImageLayout::GENERAL,
+            (true, false) => attachment.get_write_layout(),
+            _ => vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        }
+    }
+
+    /// How an attachment is accessed by a pass
+    fn get_access(&self, attachment: &AttachmentDesc, pass: &PassDesc) -> Access {
+        let mut ret = Access::default();
+        if pass.writes(&attachment.name) {
+            ret |= if attachment.is_depth() {
+                Access {
+                    stages: vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                        | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                    access: vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                        | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                }
+            } else {
+                Access {
+                    stages: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                    access: vk::AccessFlags::COLOR_ATTACHMENT_READ
+                        | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                }
+            };
+        }
+        if pass.reads(&attachment.name) {
+            ret |= Access {
+                stages: vk::PipelineStageFlags::FRAGMENT_SHADER,
+                access: vk::AccessFlags::INPUT_ATTACHMENT_READ,
+            };
+        }
+        ret
+    }
+
+    /// Works out everything needed for creating images and render passes for this graph
+    pub fn try_compile_attachments(&self) -> Result<Vec<PassAttachment>> {
+        self.validate()?;
+
+        let attachments = self
+            .attachments
+            .iter()
+            .map(|attachment| {
+                let mut usage = vk::ImageUsageFlags::empty();
+                for pass in self
+                    .passes
+                    .iter()
+                    .filter(|pass| pass.uses(&attachment.name))
+                {
+                    if pass.writes(&attachment.name) {
+                        usage |= if attachment.is_depth() {
+                            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT
+                        } else {
+                            vk::ImageUsageFlags::COLOR_ATTACHMENT
+                        };
+                    }
+                    if pass.reads(&attachment.name) {
+                        usage |= vk::ImageUsageFlags::INPUT_ATTACHMENT;
+                    }
+                }
+                if attachment.store {
+                    // So that it can be read back
+                    usage |= vk::ImageUsageFlags::TRANSFER_SRC;
+                } else {
+                    usage |= vk::ImageUsageFlags::TRANSIENT_ATTACHMENT;
+                }
+
+                let final_layout = attachment
+                    .final_layout
+                    .unwrap_or_else(|| attachment.get_write_layout());
+
+                PassAttachment {
+                    desc: attachment.clone(),
+                    usage,
+                    final_layout,
+                }
+            })
+            .collect();
+        Ok(attachments)
+    }
+
+    /// Dependencies between passes using the same attachments,
+    /// and with whatever happens before and after the render pass
+    fn get_dependencies(&self, attachments: &[PassAttachment]) -> Vec<vk::SubpassDependency> {
+        let mut dependencies: Vec<vk::SubpassDependency> = vec![];
+        let mut add_dependency = |src_subpass: u32, dst_subpass: u32, src: Access, dst: Access| {
+            let existing = dependencies.iter_mut().find(|dependency| {
+                dependency.src_subpass == src_subpass && dependency.dst_subpass == dst_subpass
+            });
+            match existing {
+                Some(dependency) => {
+                    dependency.src_stage_mask |= src.stages;
+                    dependency.src_access_mask |= src.access;
+                    dependency.dst_stage_mask |= dst.stages;
+                    dependency.dst_access_mask |= dst.access;
+                }
+                None => {
+                    // Framebuffer-local dependencies only make sense between subpasses
+                    let flags = if src_subpass == vk::SUBPASS_EXTERNAL
+                        || dst_subpass == vk::SUBPASS_EXTERNAL
+                    {
+                        vk::DependencyFlags::empty()
+                    } else {
+                        vk::DependencyFlags::BY_REGION
+                    };
+                    dependencies.push(
+                        vk::SubpassDependency::default()
+                            .src_subpass(src_subpass)
+                            .dst_subpass(dst_subpass)
+                            .src_stage_mask(src.stages)
+                            .src_access_mask(src.access)
+                            .dst_stage_mask(dst.stages)
+                            .dst_access_mask(dst.access)
+                            .dependency_flags(flags),
+                    );
+                }
+            }
+        };
+
+        // Previous frames may still be writing or reading the same images
+        let external_src = Access {
+            stages: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS
+                | vk::PipelineStageFlags::FRAGMENT_SHADER,
+            access: vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+        };
+
+        for attachment in attachments {
+            let desc = &attachment.desc;
+            let users: Vec<u32> = (0..self.passes.len() as u32)
+                .filter(|&index| self.passes[index as usize].uses(&desc.name))
+                .collect();
+            let (Some(&first), Some(&last)) = (users.first(), users.last()) else {
+                continue;
+            };
+
+            let first_access = self.get_access(desc, &self.passes[first as usize]);
+            add_dependency(vk::SUBPASS_EXTERNAL, first, external_src, first_access);
+
+            for pair in users.windows(2) {
+                let src_access = self.get_access(desc, &self.passes[pair[0] as usize]);
+                let dst_access = self.get_access(desc, &self.passes[pair[1] as usize]);
+                // Only writes need to be made available, reads just need to have finished
+                let src = Access {
+                    stages: src_access.stages,
+                    access: src_access.access
+                        & (vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                            | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE),
+                };
+                add_dependency(pair[0], pair[1], src, dst_access);
+            }
+
+            if desc.store {
+                let last_access = self.get_access(desc, &self.passes[last as usize]);
+                let dst = match attachment.final_layout {
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL => Access {
+                        stages: vk::PipelineStageFlags::FRAGMENT_SHADER,
+                        access: vk::AccessFlags::SHADER_READ,
+                    },
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL => Access {
+                        stages: vk::PipelineStageFlags::TRANSFER,
+                        access: vk::AccessFlags::TRANSFER_READ,
+                    },
+                    _ => Access {
+                        stages: vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                        access: vk::AccessFlags::MEMORY_READ,
+                    },
+                };
+                add_dependency(last, vk::SUBPASS_EXTERNAL, last_access, dst);
+            }
+        }
+
+        dependencies
+    }
+
+    // @todo Compile for dynamic rendering, once rayca-pipe builds pipelines from rendering formats
+    /// Compiles the graph into a Vulkan render pass with a subpass for each pass
+    pub fn try_compile(&self, dev: &Dev) -> Result<Pass> {
+        let attachments = self.try_compile_attachments()?;
+
+        let descriptions: Vec<vk::AttachmentDescription> = attachments
+            .iter()
+            .map(|attachment| {
+                let desc = &attachment.desc;
+                let load_op = desc.get_load_op();
+                let initial_layout = if desc.load {
+                    attachment.final_layout
+                } else {
+                    vk::ImageLayout::UNDEFINED
+                };
+                let store_op = if desc.store {
+                    vk::AttachmentStoreOp::STORE
+                } else {
+                    vk::AttachmentStoreOp::DONT_CARE
+                };
+                vk::AttachmentDescription::default()
+                    .format(desc.format)
+                    .samples(vk::SampleCountFlags::TYPE_1)
+                    .load_op(load_op)
+                    .store_op(store_op)
+                    .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+                    .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+                    .initial_layout(initial_layout)
+                    .final_layout(attachment.final_layout)
+            })
+            .collect();
+
+        let get_reference = |name: &String, pass: &PassDesc| {
+            let index = self.get_attachment_index(name).unwrap();
+            vk::AttachmentReference::default()
+                .attachment(index as u32)
+                .layout(self.get_layout(&self.attachments[index], pass))
+        };
+
+        // References need to outlive the subpass descriptions pointing to them
+        let color_refs: Vec<Vec<vk::AttachmentReference>> = self
+            .passes
+            .iter()
+            .map(|pass| {
+                pass.colors
+                    .iter()
+                    .map(|name| get_reference(name, pass))
+                    .collect()
+            })
+            .collect();
+        let input_refs: Vec<Vec<vk::AttachmentReference>> = self
+            .passes
+            .iter()
+            .map(|pass| {
+                pass.inputs
+                    .iter()
+                    .map(|name| get_reference(name, pass))
+                    .collect()
+            })
+            .collect();
+        let depth_refs: Vec<Option<vk::AttachmentReference>> = self
+            .passes
+            .iter()
+            .map(|pass| pass.depth.as_ref().map(|name| get_reference(name, pass)))
+            .collect();
+
+        let subpasses: Vec<vk::SubpassDescription> = (0..self.passes.len())
+            .map(|index| {
+                let mut subpass = vk::SubpassDescription::default()
+                    .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+                    .color_attachments(&color_refs[index])
+                    .input_attachments(&input_refs[index]);
+                if let Some(depth_ref) = &depth_refs[index] {
+                    subpass = subpass.depth_stencil_attachment(depth_ref);
+                }
+                subpass
+            })
+            .collect();
+
+        let dependencies = self.get_dependencies(&attachments);
+
+        let create_info = vk::RenderPassCreateInfo::default()
+            .attachments(&descriptions)
+            .subpasses(&subpasses)
+            .dependencies(&dependencies);
+        let render = unsafe { dev.device.create_render_pass(&create_info, None) }?;
+
+        // The layout of the external attachment, which is where the render pass leaves it
+        let present_layout = attachments
+            .iter()
+            .find(|attachment| attachment.desc.external)
+            .map(|attachment| attachment.final_layout)
+            .unwrap_or(vk::ImageLayout::UNDEFINED);
+
+        Ok(Pass {
+            render,
+            present_layout,
+            attachments,
+            passes: self.passes.clone(),
+            device: dev.device.device.clone(),
+        })
+    }
+
+    pub fn compile(&self, dev: &Dev) -> Pass {
+        self.try_compile(dev)
+            .expect("Failed to compile render graph")
+    }
+}
+
+/// An image allocated by a framebuffer for an attachment of a render graph
+pub struct FramebufferAttachment {
+    pub name: String,
+    /// Declared before the image so that it is destroyed first
+    pub view: ImageView,
+    pub image: RenderImage,
+}
+
+impl FramebufferAttachment {
+    /// Creates an image for `attachment`, recording a transition
+    /// to the layout the render pass leaves it in
+    pub fn new(
+        dev: &Dev,
+        uploader: &mut Uploader,
+        attachment: &PassAttachment,
+        extent: vk::Extent3D,
+    ) -> Self {
+        let mut image = RenderImage::new(
+            &dev.allocator,
+            extent.width,
+            extent.height,
+            attachment.desc.format,
+            attachment.usage,
+        );
+        uploader.transition_image(&mut image, attachment.final_layout);
+        let view = ImageView::new(&image);
+        Self {
+            name: attachment.desc.name.clone(),
+            view,
+            image,
+        }
+    }
+}
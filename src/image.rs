@@ -20,12 +20,110 @@ impl<R: std::io::Read> Png<R> {
     }
 }
 
+/// What the texels of an image represent, which decides whether
+/// they should be gamma-decoded when sampled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureRole {
+    /// Colors such as base color and emissive, which are stored in sRGB
+    #[default]
+    Color,
+
+    /// Non-color data such as normals, metallic-roughness, and occlusion,
+    /// which are stored linearly
+    Data,
+}
+
+impl TextureRole {
+    /// Returns the format of decoded RGBA8 images with this role
+    pub fn get_rgba8_format(self) -> vk::Format {
+        match self {
+            Self::Color => vk::Format::R8G8B8A8_SRGB,
+            Self::Data => vk::Format::R8G8B8A8_UNORM,
+        }
+    }
+}
+
+/// How images are going to be uploaded into sampled images
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ImageLoadOptions {
+    /// Whether to generate a full mip chain on the GPU. It is ignored when
+    /// the device can not blit the image format with linear filtering.
+    pub generate_mips: bool,
+
+    /// Decides between a SRGB and a UNORM format for decoded images.
+    /// KTX2 textures keep the format they have been authored with.
+    pub role: TextureRole,
+}
+
+impl ImageLoadOptions {
+    pub fn generate_mips(mut self, generate_mips: bool) -> Self {
+        self.generate_mips = generate_mips;
+        self
+    }
+
+    pub fn role(mut self, role: TextureRole) -> Self {
+        self.role = role;
+        self
+    }
+}
+
+/// Texels of an image decoded on the CPU, ready to be uploaded into a sampled image
+pub struct ImageData {
+    pub width: u32,
+    pub height: u32,
+    pub format: vk::Format,
+    /// Tightly packed texels of each mip level, starting from the base one
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl ImageData {
+    pub fn from_rgba8(image: ::image::RgbaImage, role: TextureRole) -> Self {
+        let (width, height) = image.dimensions();
+        Self {
+            width,
+            height,
+            format: role.get_rgba8_format(),
+            levels: vec![image.into_raw()],
+        }
+    }
+
+    /// Decodes KTX2 containers keeping their levels and, when possible, their compressed format.
+    /// Any other image is decoded into RGBA8 texels, guessing its format.
+    pub fn decode(device: &Device, asset: Asset, role: TextureRole) -> Result<Self> {
+        Self::decode_bytes(device, asset.into_bytes(), role)
+    }
+
+    /// Like `decode`, for the content of an asset which has already been read
+    pub fn decode_bytes(device: &Device, bytes: Vec<u8>, role: TextureRole) -> Result<Self> {
+        if Self::is_ktx2(&bytes) {
+            Self::from_ktx2(device, &bytes)
+        } else {
+            let image = RenderImage::decode_bytes(bytes)?;
+            Ok(Self::from_rgba8(image, role))
+        }
+    }
+
+    /// Returns the offset of each level, as if they were packed one after the other
+    pub fn get_level_offsets(&self) -> Vec<vk::DeviceSize> {
+        self.levels
+            .iter()
+            .scan(0, |offset, level| {
+                let level_offset = *offset;
+                *offset += level.len() as vk::DeviceSize;
+                Some(level_offset)
+            })
+            .collect()
+    }
+}
+
 pub struct RenderImage {
     /// Whether this image is manages and should be freed, or not (like swapchain images)
     managed: bool,
     pub image: vk::Image,
     pub layout: vk::ImageLayout,
     pub extent: vk::Extent3D,
+    pub mip_levels: u32,
+    pub array_layers: u32,
     pub format: vk::Format,
     pub color_space: vk::ColorSpaceKHR,
     allocation: Option<vk_mem::Allocation>,
@@ -50,6 +148,40 @@ impl RenderImage {
         }
     }
 
+    /// Returns the size in bytes of a texel of those formats which can be read back
+    pub fn get_texel_size(format: vk::Format) -> Option<usize> {
+        match format {
+            vk::Format::R8G8B8A8_UNORM
+            | vk::Format::R8G8B8A8_SRGB
+            | vk::Format::B8G8R8A8_UNORM
+            | vk::Format::B8G8R8A8_SRGB
+            | vk::Format::A2R10G10B10_UNORM_PACK32
+            | vk::Format::D32_SFLOAT => Some(4),
+            vk::Format::R16G16B16A16_SFLOAT => Some(8),
+            _ => None,
+        }
+    }
+
+    /// Converts the bits of a half precision float into a single precision one
+    fn get_f32_from_f16(bits: u16) -> f32 {
+        let negative = bits >> 15 == 1;
+        let exponent = ((bits >> 10) & 0x1F) as u32;
+        let mantissa = (bits & 0x3FF) as u32;
+        let magnitude = match exponent {
+            // Zero and subnormals have no implicit leading one
+            0 => mantissa as f32 / (1 << 24) as f32,
+            // Infinity and NaN
+            0x1F => f32::from_bits(0x7F80_0000 | (mantissa << 13)),
+            _ => f32::from_bits(((exponent + 127 - 15) << 23) | (mantissa << 13)),
+        };
+        if negative { -magnitude } else { magnitude }
+    }
+
+    /// Returns the number of levels of a full mip chain for an image of this size
+    pub fn get_mip_levels(width: u32, height: u32) -> u32 {
+        u32::BITS - width.max(height).max(1).leading_zeros()
+    }
+
     pub fn unmanaged(
         device: &Arc<Device>,
         image: vk::Image,
@@ -68,6 +200,8 @@ impl RenderImage {
             image,
             layout: vk::ImageLayout::UNDEFINED,
             extent,
+            mip_levels: 1,
+            array_layers: 1,
             format,
             color_space,
             allocation: None,
@@ -84,6 +218,41 @@ impl RenderImage {
         format: vk::Format,
         usage: vk::ImageUsageFlags,
     ) -> Self {
+        Self::try_new(allocator, width, height, format, usage)
+            .expect("Failed to create Vulkan image")
+    }
+
+    pub fn try_new(
+        allocator: &Arc<Allocator>,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<Self> {
+        Self::try_new_with_mip_levels(allocator, width, height, format, usage, 1)
+    }
+
+    pub fn try_new_with_mip_levels(
+        allocator: &Arc<Allocator>,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+        mip_levels: u32,
+    ) -> Result<Self> {
+        Self::try_new_with_layers(allocator, width, height, format, usage, mip_levels, 1)
+    }
+
+    /// Creates a 2D image with `array_layers` layers, such as a shadow atlas
+    pub fn try_new_with_layers(
+        allocator: &Arc<Allocator>,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+        mip_levels: u32,
+        array_layers: u32,
+    ) -> Result<Self> {
         let allocator = allocator.clone();
 
         // Minimum size is 1x1
@@ -95,8 +264,8 @@ impl RenderImage {
         let image_info = vk::ImageCreateInfo::default()
             .image_type(vk::ImageType::TYPE_2D)
             .extent(extent)
-            .mip_levels(1)
-            .array_layers(1)
+            .mip_levels(mip_levels)
+            .array_layers(array_layers)
             .tiling(vk::ImageTiling::OPTIMAL)
             .format(format)
             .initial_layout(vk::ImageLayout::UNDEFINED)
@@ -110,25 +279,39 @@ impl RenderImage {
         };
 
         use vk_mem::Alloc;
-        let (image, allocation) = unsafe { allocator.create_image(&image_info, &alloc_info) }
-            .expect("Failed to create Vulkan image");
+        // Transient attachments may never leave tile memory, when lazily allocated memory is available
+        let lazy_alloc_info = vk_mem::AllocationCreateInfo {
+            usage: vk_mem::MemoryUsage::GpuLazy,
+            ..Default::default()
+        };
+        let lazy = usage
+            .contains(vk::ImageUsageFlags::TRANSIENT_ATTACHMENT)
+            .then(|| unsafe { allocator.create_image(&image_info, &lazy_alloc_info) }.ok())
+            .flatten();
+        let (image, allocation) = match lazy {
+            Some(lazy) => lazy,
+            None => unsafe { allocator.create_image(&image_info, &alloc_info) }?,
+        };
 
         let device = allocator.device.clone();
 
-        Self {
+        Ok(Self {
             managed: true,
             image,
             layout: vk::ImageLayout::UNDEFINED,
             extent,
+            mip_levels,
+            array_layers,
             format,
             color_space: vk::ColorSpaceKHR::default(),
             allocation: Some(allocation),
             allocator: Some(allocator),
             device,
-        }
+        })
     }
 
-    /// Create an image that can be used as an input or output attachment
+    /// Create an image that can be used as an input or output attachment,
+    /// and that can be read back into host memory
     pub fn attachment(
         allocator: &Arc<Allocator>,
         width: u32,
@@ -139,7 +322,7 @@ impl RenderImage {
             vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | vk::ImageUsageFlags::INPUT_ATTACHMENT
         } else {
             vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::INPUT_ATTACHMENT
-        };
+        } | vk::ImageUsageFlags::TRANSFER_SRC;
         Self::new(allocator, width, height, format, usage)
     }
 
@@ -150,53 +333,167 @@ impl RenderImage {
         height: u32,
         format: vk::Format,
     ) -> Self {
-        Self::new(
+        Self::try_sampled(allocator, width, height, format).expect("Failed to create Vulkan image")
+    }
+
+    pub fn try_sampled(
+        allocator: &Arc<Allocator>,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+    ) -> Result<Self> {
+        Self::try_sampled_with_options(
             allocator,
             width,
             height,
             format,
-            vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
+            &ImageLoadOptions::default(),
         )
     }
 
-    /// Creates a new image from raw data uploading it into a sampled image
-    pub fn from_data(
+    /// Create a sampled image with a full mip chain, if requested and supported by the device
+    pub fn sampled_with_options(
         allocator: &Arc<Allocator>,
-        graphics_queue: &GraphicsQueue,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        options: &ImageLoadOptions,
+    ) -> Self {
+        Self::try_sampled_with_options(allocator, width, height, format, options)
+            .expect("Failed to create Vulkan image")
+    }
+
+    pub fn try_sampled_with_options(
+        allocator: &Arc<Allocator>,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        options: &ImageLoadOptions,
+    ) -> Result<Self> {
+        let mut usage = vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED;
+        let mut mip_levels = 1;
+
+        if options.generate_mips && allocator.device.supports_linear_blit(format) {
+            // Levels are blitted from one another
+            usage |= vk::ImageUsageFlags::TRANSFER_SRC;
+            mip_levels = Self::get_mip_levels(width, height);
+        }
+
+        Self::try_new_with_mip_levels(allocator, width, height, format, usage, mip_levels)
+    }
+
+    /// Create a sampled image with all the levels of `data`, or
+    /// with a mip chain to generate if requested when it has only one
+    pub fn try_sampled_for_data(
+        allocator: &Arc<Allocator>,
+        data: &ImageData,
+        options: &ImageLoadOptions,
+    ) -> Result<Self> {
+        if data.levels.len() > 1 {
+            let usage = vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED;
+            let mip_levels = data.levels.len() as u32;
+            Self::try_new_with_mip_levels(
+                allocator,
+                data.width,
+                data.height,
+                data.format,
+                usage,
+                mip_levels,
+            )
+        } else {
+            Self::try_sampled_with_options(allocator, data.width, data.height, data.format, options)
+        }
+    }
+
+    /// Creates a new sampled image recording the upload of all the levels of `data`.
+    /// The image can be used once the token returned by `Uploader::submit` is ready.
+    pub fn try_from_image_data(
+        uploader: &mut Uploader,
+        data: &ImageData,
+        options: &ImageLoadOptions,
+    ) -> Result<Self> {
+        let mut image = Self::try_sampled_for_data(uploader.get_allocator(), data, options)?;
+        uploader.upload_image_data(&mut image, data)?;
+        Ok(image)
+    }
+
+    /// Creates a new sampled image recording the upload of raw data
+    pub fn from_data(
+        uploader: &mut Uploader,
         data: &[u8],
         width: u32,
         height: u32,
         format: vk::Format,
     ) -> Self {
-        let mut image = Self::sampled(allocator, width, height, format);
-        let usage = vk::BufferUsageFlags::TRANSFER_SRC;
-        let staging = RenderBuffer::from_data(allocator, data, usage);
-        image.simple_copy_from(&staging, graphics_queue);
-        image
+        Self::from_data_with_options(
+            uploader,
+            data,
+            width,
+            height,
+            format,
+            &ImageLoadOptions::default(),
+        )
     }
 
-    /// Loads a PNG image from file and uploads it into a sampled image
-    pub fn load(allocator: &Arc<Allocator>, graphics_queue: &GraphicsQueue, asset: Asset) -> Self {
-        let image_reader = ::image::ImageReader::new(std::io::Cursor::new(asset.into_bytes()))
-            .with_guessed_format()
-            .expect("Failed to guess image format")
-            .decode()
-            .expect("Failed to decode image");
-        let rgba8_image = image_reader.into_rgba8();
-        let dim = rgba8_image.dimensions();
-        let staging = RenderBuffer::load(allocator, rgba8_image);
-
-        let format = vk::Format::R8G8B8A8_SRGB;
-        let mut image = Self::sampled(allocator, dim.0, dim.1, format);
-        image.simple_copy_from(&staging, graphics_queue);
+    pub fn from_data_with_options(
+        uploader: &mut Uploader,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        options: &ImageLoadOptions,
+    ) -> Self {
+        let allocator = uploader.get_allocator();
+        let mut image = Self::sampled_with_options(allocator, width, height, format, options);
+        uploader
+            .upload_image(&mut image, data)
+            .expect("Failed to upload image");
         image
     }
 
-    pub fn transition(&mut self, graphics_queue: &GraphicsQueue, new_layout: vk::ImageLayout) {
-        // @todo Use TRANSFER pool and transfer queue?
-        let command_buffer = CommandBuffer::new(&graphics_queue.command_pool);
-        command_buffer.begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+    /// Decodes an image asset into RGBA8 texels, guessing its format
+    pub fn decode(asset: Asset) -> Result<::image::RgbaImage> {
+        Self::decode_bytes(asset.into_bytes())
+    }
+
+    fn decode_bytes(bytes: Vec<u8>) -> Result<::image::RgbaImage> {
+        let image = ::image::ImageReader::new(std::io::Cursor::new(bytes))
+            .with_guessed_format()?
+            .decode()?;
+        Ok(image.into_rgba8())
+    }
+
+    /// Loads an image from file and records its upload into a sampled image.
+    /// KTX2 containers keep their levels and compressed format when supported.
+    pub fn load(uploader: &mut Uploader, asset: Asset) -> Self {
+        Self::try_load(uploader, asset).expect("Failed to load image")
+    }
 
+    pub fn load_with_options(
+        uploader: &mut Uploader,
+        asset: Asset,
+        options: &ImageLoadOptions,
+    ) -> Self {
+        Self::try_load_with_options(uploader, asset, options).expect("Failed to load image")
+    }
+
+    /// Like `load`, but returns an error when the image can not be decoded
+    /// instead of panicking, so that a corrupt texture can be skipped
+    pub fn try_load(uploader: &mut Uploader, asset: Asset) -> Result<Self> {
+        Self::try_load_with_options(uploader, asset, &ImageLoadOptions::default())
+    }
+
+    pub fn try_load_with_options(
+        uploader: &mut Uploader,
+        asset: Asset,
+        options: &ImageLoadOptions,
+    ) -> Result<Self> {
+        let data = ImageData::decode(&uploader.get_allocator().device, asset, options.role)?;
+        Self::try_from_image_data(uploader, &data, options)
+    }
+
+    /// Records a transition of all levels and layers to `new_layout`
+    pub fn transition(&mut self, command_buffer: &CommandBuffer, new_layout: vk::ImageLayout) {
         // Old layout -> New layout
         let src_stage_mask = vk::PipelineStageFlags::TOP_OF_PIPE;
         let dst_stage_mask = vk::PipelineStageFlags::TRANSFER;
@@ -210,9 +507,9 @@ impl RenderImage {
                     vk::ImageSubresourceRange::default()
                         .aspect_mask(Self::get_aspect_from_format(self.format))
                         .base_mip_level(0)
-                        .level_count(1)
+                        .level_count(self.mip_levels)
                         .base_array_layer(0)
-                        .layer_count(1),
+                        .layer_count(self.array_layers),
                 )
                 .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE),
         ];
@@ -224,37 +521,27 @@ impl RenderImage {
         );
 
         self.layout = new_layout;
-
-        command_buffer.end();
-
-        let mut fence = Fence::unsignaled(&graphics_queue.command_pool.device);
-
-        let commands = [command_buffer.command_buffer];
-        let submits = [vk::SubmitInfo::default().command_buffers(&commands)];
-        graphics_queue.submit(&submits, Some(&mut fence));
-
-        fence.wait();
     }
 
-    pub fn simple_copy_from(&mut self, staging: &RenderBuffer, graphics_queue: &GraphicsQueue) {
-        // @todo Use TRANSFER pool and transfer queue
-        let command_buffer = CommandBuffer::new(&graphics_queue.command_pool);
-        command_buffer.begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
-
-        self.copy_from(staging, &command_buffer);
-
-        command_buffer.end();
-
-        let mut fence = Fence::unsignaled(&graphics_queue.command_pool.device);
-
-        let commands = [command_buffer.command_buffer];
-        let submits = [vk::SubmitInfo::default().command_buffers(&commands)];
-        graphics_queue.submit(&submits, Some(&mut fence));
+    /// Copies `staging` into the base level and generates the other levels, if any
+    pub fn copy_from(&mut self, staging: &RenderBuffer, command_buffer: &CommandBuffer) {
+        self.copy_base_level_from(staging, command_buffer);
+        self.generate_mips(command_buffer);
+    }
 
-        fence.wait();
+    /// Transitions all levels to transfer dst optimal and copies `staging` into the base level
+    pub fn copy_base_level_from(&mut self, staging: &RenderBuffer, command_buffer: &CommandBuffer) {
+        self.copy_levels_from(staging, &[0], command_buffer);
     }
 
-    pub fn copy_from(&mut self, staging: &RenderBuffer, command_buffer: &CommandBuffer) {
+    /// Transitions all levels to transfer dst optimal and copies `staging` into the first levels,
+    /// where each level starts at the corresponding offset of `level_offsets`
+    pub fn copy_levels_from(
+        &mut self,
+        staging: &RenderBuffer,
+        level_offsets: &[vk::DeviceSize],
+        command_buffer: &CommandBuffer,
+    ) {
         // Undefined -> Transfer dst optimal
         let new_layout = vk::ImageLayout::TRANSFER_DST_OPTIMAL;
 
@@ -270,9 +557,9 @@ impl RenderImage {
                     vk::ImageSubresourceRange::default()
                         .aspect_mask(vk::ImageAspectFlags::COLOR)
                         .base_mip_level(0)
-                        .level_count(1)
+                        .level_count(self.mip_levels)
                         .base_array_layer(0)
-                        .layer_count(1),
+                        .layer_count(self.array_layers),
                 )
                 .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE),
         ];
@@ -286,45 +573,296 @@ impl RenderImage {
         self.layout = new_layout;
 
         // Copy
-        let region = vk::BufferImageCopy::default()
-            .image_subresource(
-                vk::ImageSubresourceLayers::default()
-                    .aspect_mask(vk::ImageAspectFlags::COLOR)
-                    .layer_count(1),
-            )
-            .image_extent(self.extent);
-        command_buffer.copy_buffer_to_image(staging, self, &region);
+        for (level, offset) in level_offsets.iter().enumerate() {
+            let level = level as u32;
+            let region = vk::BufferImageCopy::default()
+                .buffer_offset(*offset)
+                .image_subresource(
+                    vk::ImageSubresourceLayers::default()
+                        .aspect_mask(vk::ImageAspectFlags::COLOR)
+                        .mip_level(level)
+                        .layer_count(1),
+                )
+                .image_extent(self.get_level_extent(level));
+            command_buffer.copy_buffer_to_image(staging, self, &region);
+        }
+    }
+
+    pub fn get_level_extent(&self, level: u32) -> vk::Extent3D {
+        vk::Extent3D::default()
+            .width((self.extent.width >> level).max(1))
+            .height((self.extent.height >> level).max(1))
+            .depth(1)
+    }
 
-        // Transfer dst optimal -> Shader read only optimal
-        let new_layout = vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL;
+    /// Expects all levels to be transfer dst optimal, with the base level already filled.
+    /// Each level is blitted into the next one, and all of them are left shader read only
+    /// optimal, which is the only thing that happens for images with a single level.
+    pub fn generate_mips(&mut self, command_buffer: &CommandBuffer) {
+        self.generate_mips_from(1, command_buffer);
+    }
 
-        let src_stage_mask = vk::PipelineStageFlags::TRANSFER;
-        let dst_stage_mask = vk::PipelineStageFlags::FRAGMENT_SHADER;
-        let dependency_flags = vk::DependencyFlags::default();
-        let image_memory_barriers = vec![
+    /// Like `generate_mips`, but expects the levels before `first_level` to be already filled,
+    /// so that only the following ones are blitted
+    pub fn generate_mips_from(&mut self, first_level: u32, command_buffer: &CommandBuffer) {
+        assert_eq!(self.layout, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+        let first_level = first_level.clamp(1, self.mip_levels);
+
+        let levels_barrier = |level: u32, level_count: u32| {
             vk::ImageMemoryBarrier::default()
-                .old_layout(self.layout)
-                .new_layout(new_layout)
                 .image(self.image)
                 .subresource_range(
                     vk::ImageSubresourceRange::default()
                         .aspect_mask(vk::ImageAspectFlags::COLOR)
-                        .base_mip_level(0)
-                        .level_count(1)
+                        .base_mip_level(level)
+                        .level_count(level_count)
                         .base_array_layer(0)
                         .layer_count(1),
                 )
+        };
+        let level_barrier = |level: u32| levels_barrier(level, 1);
+        let level_layers = |level: u32| {
+            vk::ImageSubresourceLayers::default()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .mip_level(level)
+                .layer_count(1)
+        };
+
+        if first_level > 1 {
+            // Filled levels which are not going to be blitted from:
+            // Transfer dst optimal -> Shader read only optimal
+            let image_memory_barriers = vec![
+                levels_barrier(0, first_level - 1)
+                    .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                    .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                    .dst_access_mask(vk::AccessFlags::SHADER_READ),
+            ];
+            command_buffer.pipeline_barriers(
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::default(),
+                &image_memory_barriers,
+            );
+        }
+
+        let first_extent = self.get_level_extent(first_level - 1);
+        let mut width = first_extent.width as i32;
+        let mut height = first_extent.height as i32;
+
+        for level in first_level..self.mip_levels {
+            // Previous level: Transfer dst optimal -> Transfer src optimal
+            let image_memory_barriers = vec![
+                level_barrier(level - 1)
+                    .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                    .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+                    .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                    .dst_access_mask(vk::AccessFlags::TRANSFER_READ),
+            ];
+            command_buffer.pipeline_barriers(
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::default(),
+                &image_memory_barriers,
+            );
+
+            let next_width = (width / 2).max(1);
+            let next_height = (height / 2).max(1);
+            let region = vk::ImageBlit::default()
+                .src_subresource(level_layers(level - 1))
+                .src_offsets([
+                    vk::Offset3D::default(),
+                    vk::Offset3D::default().x(width).y(height).z(1),
+                ])
+                .dst_subresource(level_layers(level))
+                .dst_offsets([
+                    vk::Offset3D::default(),
+                    vk::Offset3D::default().x(next_width).y(next_height).z(1),
+                ]);
+            command_buffer.blit_image(
+                self,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                self,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &region,
+                vk::Filter::LINEAR,
+            );
+
+            // Previous level: Transfer src optimal -> Shader read only optimal
+            let image_memory_barriers = vec![
+                level_barrier(level - 1)
+                    .old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+                    .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .src_access_mask(vk::AccessFlags::TRANSFER_READ)
+                    .dst_access_mask(vk::AccessFlags::SHADER_READ),
+            ];
+            command_buffer.pipeline_barriers(
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::default(),
+                &image_memory_barriers,
+            );
+
+            width = next_width;
+            height = next_height;
+        }
+
+        // Last level: Transfer dst optimal -> Shader read only optimal
+        let image_memory_barriers = vec![
+            level_barrier(self.mip_levels - 1)
+                .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
                 .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
                 .dst_access_mask(vk::AccessFlags::SHADER_READ),
         ];
         command_buffer.pipeline_barriers(
-            src_stage_mask,
-            dst_stage_mask,
-            dependency_flags,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::DependencyFlags::default(),
+            &image_memory_barriers,
+        );
+
+        self.layout = vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL;
+    }
+
+    /// Copies the content of this image into host memory and waits for the copy to finish.
+    /// The image is expected to be in its current `layout` and it is left in that layout.
+    pub fn download(
+        &mut self,
+        allocator: &Arc<Allocator>,
+        graphics_queue: &GraphicsQueue,
+    ) -> Vec<u8> {
+        let texel_size = Self::get_texel_size(self.format)
+            .unwrap_or_else(|| panic!("Can not read back {:?} images", self.format));
+        let size = self.extent.width as usize * self.extent.height as usize * texel_size;
+        let mut staging = RenderBuffer::readback(allocator, size as vk::DeviceSize);
+
+        let command_buffer = CommandBuffer::new(&graphics_queue.command_pool);
+        command_buffer.begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+        let aspect = Self::get_aspect_from_format(self.format);
+        // Only the base level is copied, but the layout is tracked for all of them
+        let subresource_range = vk::ImageSubresourceRange::default()
+            .aspect_mask(aspect)
+            .base_mip_level(0)
+            .level_count(self.mip_levels)
+            .base_array_layer(0)
+            .layer_count(1);
+
+        // Current layout -> Transfer src optimal, waiting for any previous write
+        let previous_layout = self.layout;
+        let new_layout = vk::ImageLayout::TRANSFER_SRC_OPTIMAL;
+
+        let image_memory_barriers = vec![
+            vk::ImageMemoryBarrier::default()
+                .old_layout(previous_layout)
+                .new_layout(new_layout)
+                .image(self.image)
+                .subresource_range(subresource_range)
+                .src_access_mask(vk::AccessFlags::MEMORY_WRITE)
+                .dst_access_mask(vk::AccessFlags::TRANSFER_READ),
+        ];
+        command_buffer.pipeline_barriers(
+            vk::PipelineStageFlags::ALL_COMMANDS,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::default(),
             &image_memory_barriers,
         );
 
         self.layout = new_layout;
+
+        // Copy
+        let region = vk::BufferImageCopy::default()
+            .image_subresource(
+                vk::ImageSubresourceLayers::default()
+                    .aspect_mask(aspect)
+                    .layer_count(1),
+            )
+            .image_extent(self.extent);
+        command_buffer.copy_image_to_buffer(self, &staging, &region);
+
+        // Transfer src optimal -> Previous layout, unless there was no layout at all
+        if previous_layout != vk::ImageLayout::UNDEFINED {
+            let image_memory_barriers = vec![
+                vk::ImageMemoryBarrier::default()
+                    .old_layout(self.layout)
+                    .new_layout(previous_layout)
+                    .image(self.image)
+                    .subresource_range(subresource_range)
+                    .src_access_mask(vk::AccessFlags::TRANSFER_READ)
+                    .dst_access_mask(vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE),
+            ];
+            command_buffer.pipeline_barriers(
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+                vk::DependencyFlags::default(),
+                &image_memory_barriers,
+            );
+
+            self.layout = previous_layout;
+        }
+
+        command_buffer.end();
+        graphics_queue.submit_and_wait(&command_buffer);
+
+        staging.download()
+    }
+
+    /// Reads back this image as 8-bit RGBA texels
+    pub fn read_rgba8(
+        &mut self,
+        allocator: &Arc<Allocator>,
+        graphics_queue: &GraphicsQueue,
+    ) -> ::image::RgbaImage {
+        let mut data = self.download(allocator, graphics_queue);
+        match self.format {
+            vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_SRGB => (),
+            vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => {
+                data.chunks_exact_mut(4).for_each(|texel| texel.swap(0, 2))
+            }
+            format => panic!("Can not read back {:?} images as RGBA8", format),
+        }
+        ::image::RgbaImage::from_raw(self.extent.width, self.extent.height, data).unwrap()
+    }
+
+    /// Reads back this image as floating point RGBA texels.
+    /// Depth values are replicated into the RGB channels.
+    pub fn read_rgba32f(
+        &mut self,
+        allocator: &Arc<Allocator>,
+        graphics_queue: &GraphicsQueue,
+    ) -> ::image::Rgba32FImage {
+        let format = self.format;
+        let texel_size = Self::get_texel_size(format).unwrap();
+        let data = self.download(allocator, graphics_queue);
+        let texels = data.chunks_exact(texel_size).flat_map(|texel| {
+            let bits = u32::from_le_bytes([texel[0], texel[1], texel[2], texel[3]]);
+            match format {
+                vk::Format::A2R10G10B10_UNORM_PACK32 => [
+                    ((bits >> 20) & 0x3FF) as f32 / 1023.0,
+                    ((bits >> 10) & 0x3FF) as f32 / 1023.0,
+                    (bits & 0x3FF) as f32 / 1023.0,
+                    (bits >> 30) as f32 / 3.0,
+                ],
+                vk::Format::R16G16B16A16_SFLOAT => [0, 2, 4, 6].map(|i| {
+                    let half = u16::from_le_bytes([texel[i], texel[i + 1]]);
+                    Self::get_f32_from_f16(half)
+                }),
+                vk::Format::D32_SFLOAT => {
+                    let depth = f32::from_bits(bits);
+                    [depth, depth, depth, 1.0]
+                }
+                vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_SRGB => {
+                    [texel[0], texel[1], texel[2], texel[3]].map(|channel| channel as f32 / 255.0)
+                }
+                vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => {
+                    [texel[2], texel[1], texel[0], texel[3]].map(|channel| channel as f32 / 255.0)
+                }
+                format => panic!("Can not read back {:?} images as RGBA32F", format),
+            }
+        });
+        ::image::Rgba32FImage::from_raw(self.extent.width, self.extent.height, texels.collect())
+            .unwrap()
     }
 }
 
@@ -347,19 +885,29 @@ pub struct ImageView {
 
 impl ImageView {
     pub fn new(image: &RenderImage) -> Self {
+        Self::new_with_layers(image, vk::ImageViewType::TYPE_2D, 0, 1)
+    }
+
+    /// Creates a view into `layer_count` layers of `image`, starting from `base_array_layer`
+    pub fn new_with_layers(
+        image: &RenderImage,
+        view_type: vk::ImageViewType,
+        base_array_layer: u32,
+        layer_count: u32,
+    ) -> Self {
         let aspect = RenderImage::get_aspect_from_format(image.format);
 
         let create_info = vk::ImageViewCreateInfo::default()
             .image(image.image)
-            .view_type(vk::ImageViewType::TYPE_2D)
+            .view_type(view_type)
             .format(image.format)
             .subresource_range(
                 vk::ImageSubresourceRange::default()
                     .aspect_mask(aspect)
                     .base_mip_level(0)
-                    .level_count(1)
-                    .base_array_layer(0)
-                    .layer_count(1),
+                    .level_count(image.mip_levels)
+                    .base_array_layer(base_array_layer)
+                    .layer_count(layer_count),
             );
 
         let view = unsafe { image.device.create_image_view(&create_info, None) }
@@ -380,7 +928,7 @@ impl Drop for ImageView {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 pub struct RenderTexture {
     pub view: vk::ImageView,
     pub sampler: vk::Sampler,
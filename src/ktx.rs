@@ -0,0 +1,196 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use crate::*;
+
+/// Identifier at the start of every KTX2 file
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+impl ImageData {
+    pub fn is_ktx2(bytes: &[u8]) -> bool {
+        bytes.starts_with(&KTX2_IDENTIFIER)
+    }
+
+    /// Reads all the levels of a 2D KTX2 texture. Formats such as BC7, BC5, ETC2, and ASTC
+    /// are kept as they are, and need to be supported by the device. Basis Universal UASTC
+    /// textures are transcoded into a supported format when the `basisu` feature is enabled.
+    pub fn from_ktx2(device: &Device, bytes: &[u8]) -> Result<Self> {
+        let reader = ::ktx2::Reader::new(bytes)?;
+        let header = reader.header();
+        if header.pixel_depth > 1 || header.layer_count > 1 || header.face_count > 1 {
+            return Err(Error::Unsupported(
+                "KTX2 volume, array, and cube textures".to_string(),
+            ));
+        }
+
+        let levels = reader
+            .levels()
+            .map(|level| Self::inflate_ktx2_level(header.supercompression_scheme, level))
+            .collect::<Result<Vec<_>>>()?;
+
+        let width = header.pixel_width;
+        // Height is zero for 1D textures
+        let height = header.pixel_height.max(1);
+
+        let Some(format) = header.format else {
+            // Basis Universal data has an undefined format
+            return Self::transcode_ktx2(device, &reader, width, height, levels);
+        };
+
+        // KTX2 formats are Vulkan formats
+        let format = vk::Format::from_raw(format.value() as i32);
+        if !device.supports_sampled_format(format) {
+            return Err(Error::Unsupported(format!("Sampling {:?} images", format)));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            format,
+            levels,
+        })
+    }
+
+    fn inflate_ktx2_level(
+        scheme: Option<::ktx2::SupercompressionScheme>,
+        level: ::ktx2::Level,
+    ) -> Result<Vec<u8>> {
+        match scheme {
+            None => Ok(level.data.to_vec()),
+            Some(::ktx2::SupercompressionScheme::Zstandard) => Ok(zstd::bulk::decompress(
+                level.data,
+                level.uncompressed_byte_length as usize,
+            )?),
+            Some(scheme) => Err(Error::Unsupported(format!(
+                "KTX2 {:?} supercompression",
+                scheme
+            ))),
+        }
+    }
+
+    #[cfg(feature = "basisu")]
+    fn transcode_ktx2(
+        device: &Device,
+        reader: &::ktx2::Reader<&[u8]>,
+        width: u32,
+        height: u32,
+        levels: Vec<Vec<u8>>,
+    ) -> Result<Self> {
+        use basis_universal::{
+            DecodeFlags, LowLevelUastcTranscoder, SliceParametersUastc, transcoder_init,
+        };
+
+        let block = reader.dfd_blocks().next().ok_or_else(|| {
+            Error::InvalidImage("KTX2 texture without data format descriptor".to_string())
+        })?;
+        let dfd = ::ktx2::DfdBlockBasic::parse(block.data)?;
+        if dfd.header.color_model != Some(::ktx2::ColorModel::UASTC) {
+            return Err(Error::Unsupported(format!(
+                "Transcoding KTX2 {:?} textures",
+                dfd.header.color_model
+            )));
+        }
+
+        let srgb = dfd.header.transfer_function == Some(::ktx2::TransferFunction::SRGB);
+        // UASTC channel IDs of RGBA and RRRG data
+        let has_alpha = dfd
+            .sample_information()
+            .any(|sample| matches!(sample.channel_type, 3 | 5));
+        let (format, block_format) = Self::get_transcode_target(device, srgb);
+
+        transcoder_init();
+        let transcoder = LowLevelUastcTranscoder::new();
+
+        let levels = levels
+            .iter()
+            .enumerate()
+            .map(|(level, data)| {
+                let level_width = (width >> level).max(1);
+                let level_height = (height >> level).max(1);
+                let slice_parameters = SliceParametersUastc {
+                    num_blocks_x: level_width.div_ceil(4),
+                    num_blocks_y: level_height.div_ceil(4),
+                    has_alpha,
+                    original_width: level_width,
+                    original_height: level_height,
+                };
+                transcoder
+                    .transcode_slice(
+                        data,
+                        slice_parameters,
+                        DecodeFlags::HIGH_QUALITY,
+                        block_format,
+                    )
+                    .map_err(|_| {
+                        Error::InvalidImage(format!("Failed to transcode UASTC level {}", level))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            width,
+            height,
+            format,
+            levels,
+        })
+    }
+
+    #[cfg(not(feature = "basisu"))]
+    fn transcode_ktx2(
+        _device: &Device,
+        _reader: &::ktx2::Reader<&[u8]>,
+        _width: u32,
+        _height: u32,
+        _levels: Vec<Vec<u8>>,
+    ) -> Result<Self> {
+        Err(Error::Unsupported(
+            "Basis Universal textures without the basisu feature".to_string(),
+        ))
+    }
+
+    /// Returns the first compressed format supported by the device which UASTC
+    /// can be transcoded into, falling back to uncompressed RGBA8
+    #[cfg(feature = "basisu")]
+    fn get_transcode_target(
+        device: &Device,
+        srgb: bool,
+    ) -> (vk::Format, basis_universal::TranscoderBlockFormat) {
+        use basis_universal::TranscoderBlockFormat;
+
+        let candidates = [
+            (
+                vk::Format::BC7_SRGB_BLOCK,
+                vk::Format::BC7_UNORM_BLOCK,
+                TranscoderBlockFormat::BC7,
+            ),
+            (
+                vk::Format::ASTC_4X4_SRGB_BLOCK,
+                vk::Format::ASTC_4X4_UNORM_BLOCK,
+                TranscoderBlockFormat::ASTC_4x4,
+            ),
+            (
+                vk::Format::ETC2_R8G8B8A8_SRGB_BLOCK,
+                vk::Format::ETC2_R8G8B8A8_UNORM_BLOCK,
+                TranscoderBlockFormat::ETC2_RGBA,
+            ),
+        ];
+
+        let fallback = if srgb {
+            vk::Format::R8G8B8A8_SRGB
+        } else {
+            vk::Format::R8G8B8A8_UNORM
+        };
+
+        candidates
+            .into_iter()
+            .map(|(srgb_format, unorm_format, block_format)| {
+                let format = if srgb { srgb_format } else { unorm_format };
+                (format, block_format)
+            })
+            .find(|(format, _)| device.supports_sampled_format(*format))
+            .unwrap_or((fallback, TranscoderBlockFormat::RGBA32))
+    }
+}
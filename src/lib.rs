@@ -2,10 +2,14 @@
 // Author: Antonio Caggiano <info@antoniocaggiano.eu>
 // SPDX-License-Identifier: MIT
 
+mod action;
+pub use action::*;
 mod buffer;
 pub use buffer::*;
 mod command;
 pub use command::*;
+mod controller;
+pub use controller::*;
 mod ctx;
 pub use ctx::*;
 mod debug;
@@ -14,6 +18,10 @@ mod device;
 pub use device::*;
 mod descriptor;
 pub use descriptor::*;
+mod error;
+pub use error::*;
+mod extension;
+pub use extension::*;
 mod events;
 pub use events::*;
 mod fallback;
@@ -22,18 +30,33 @@ mod frame;
 pub use frame::*;
 mod win;
 pub use win::*;
+mod gamepad;
+pub use gamepad::*;
 mod gfx;
 pub use gfx::*;
+mod graph;
+pub use graph::*;
 mod image;
 pub use image::*;
+mod ktx;
+mod light;
+pub use light::*;
+mod lighting;
+pub use lighting::*;
 mod model;
 pub use model::*;
 mod pass;
 pub use pass::*;
+mod pbr;
+pub use pbr::*;
+mod physical;
+pub use physical::*;
 mod pipeline;
 pub use pipeline::*;
 mod primitive;
 pub use primitive::*;
+mod projection;
+pub use projection::*;
 mod queue;
 pub use queue::*;
 mod sampler;
@@ -50,8 +73,14 @@ mod swapchain;
 pub use swapchain::*;
 mod shader;
 pub use shader::*;
+mod shadow;
+pub use shadow::*;
 mod sync;
 pub use sync::*;
+mod touch;
+pub use touch::*;
+mod upload;
+pub use upload::*;
 
 pub use ash;
 pub use ash::vk;
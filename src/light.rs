@@ -0,0 +1,199 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use crate::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightKind {
+    /// Infinitely far away, emitting light along the local -Z axis of its node
+    Directional,
+
+    /// Emits light in all directions from the position of its node
+    Point,
+
+    /// Emits light in a cone along the local -Z axis of its node.
+    /// Angles are in radians from the center of the cone.
+    Spot {
+        inner_cone_angle: f32,
+        outer_cone_angle: f32,
+    },
+}
+
+impl LightKind {
+    /// Value of the `kind` field of `LightData`, as understood by the lighting shader
+    pub fn get_index(&self) -> u32 {
+        match self {
+            Self::Directional => 0,
+            Self::Point => 1,
+            Self::Spot { .. } => 2,
+        }
+    }
+}
+
+/// A light as described by `KHR_lights_punctual`, which takes
+/// its position and direction from the node it is attached to
+#[derive(Clone, Debug)]
+pub struct PunctualLight {
+    pub kind: LightKind,
+    pub color: Color,
+    /// Lux for directional lights, candela for point and spot lights
+    pub intensity: f32,
+    /// Distance where the light reaches zero, `None` means infinite
+    pub range: Option<f32>,
+    /// Only directional and spot lights cast shadows, `None` means no shadows
+    pub shadow: Option<ShadowSettings>,
+}
+
+impl PunctualLight {
+    pub fn new(kind: LightKind) -> Self {
+        Self {
+            kind,
+            color: Color::WHITE,
+            intensity: 1.0,
+            range: None,
+            shadow: None,
+        }
+    }
+
+    pub fn directional() -> Self {
+        Self::new(LightKind::Directional)
+    }
+
+    pub fn point() -> Self {
+        Self::new(LightKind::Point)
+    }
+
+    pub fn spot(inner_cone_angle: f32, outer_cone_angle: f32) -> Self {
+        Self::new(LightKind::Spot {
+            inner_cone_angle,
+            outer_cone_angle,
+        })
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn range(mut self, range: f32) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn shadow(mut self, shadow: ShadowSettings) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Whether this light renders shadow maps, point lights do not
+    pub fn casts_shadows(&self) -> bool {
+        self.shadow.is_some() && !matches!(self.kind, LightKind::Point)
+    }
+}
+
+impl From<&::gltf::khr_lights_punctual::Light<'_>> for PunctualLight {
+    fn from(light: &::gltf::khr_lights_punctual::Light) -> Self {
+        use ::gltf::khr_lights_punctual::Kind;
+
+        let kind = match light.kind() {
+            Kind::Directional => LightKind::Directional,
+            Kind::Point => LightKind::Point,
+            Kind::Spot {
+                inner_cone_angle,
+                outer_cone_angle,
+            } => LightKind::Spot {
+                inner_cone_angle,
+                outer_cone_angle,
+            },
+        };
+        let [r, g, b] = light.color();
+        Self {
+            kind,
+            color: Color::new(r, g, b, 1.0),
+            intensity: light.intensity(),
+            range: light.range(),
+            shadow: None,
+        }
+    }
+}
+
+/// A light as laid out in the storage buffer of the lighting pipeline
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct LightData {
+    /// World transform of the node, the shader takes position and direction from it
+    pub model: Mat4,
+    /// The alpha component is the intensity
+    pub color: [f32; 4],
+    pub kind: u32,
+    /// Zero when the range is infinite
+    pub range: f32,
+    /// Spot cone attenuation is `saturate(cos_angle * scale + offset)`
+    pub cone_scale: f32,
+    pub cone_offset: f32,
+    /// First shadow of this light in the shadow buffer, or -1 when it casts none
+    pub shadow_index: i32,
+    /// Number of cascades of directional lights, one for spot lights
+    pub shadow_count: u32,
+    pub shadow_bias: f32,
+    pub shadow_normal_bias: f32,
+}
+
+impl LightData {
+    pub fn new(light: &PunctualLight, world_trs: &Trs) -> Self {
+        let (cone_scale, cone_offset) = match light.kind {
+            LightKind::Spot {
+                inner_cone_angle,
+                outer_cone_angle,
+            } => {
+                let cos_inner = inner_cone_angle.cos();
+                let cos_outer = outer_cone_angle.cos();
+                let scale = 1.0 / (cos_inner - cos_outer).max(0.001);
+                (scale, -cos_outer * scale)
+            }
+            _ => (0.0, 1.0),
+        };
+
+        Self {
+            model: world_trs.to_mat4(),
+            color: [light.color.r, light.color.g, light.color.b, light.intensity],
+            kind: light.kind.get_index(),
+            range: light.range.unwrap_or(0.0),
+            cone_scale,
+            cone_offset,
+            shadow_index: -1,
+            shadow_count: 0,
+            shadow_bias: 0.0,
+            shadow_normal_bias: 0.0,
+        }
+    }
+}
+
+/// Scene-wide lighting parameters as laid out in the uniform buffer of the lighting pipeline
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct LightingData {
+    /// The alpha component is not used
+    pub ambient: [f32; 4],
+    pub exposure: f32,
+    pub light_count: u32,
+    _padding: [f32; 2],
+}
+
+impl LightingData {
+    pub fn new(scene: &RenderScene, light_count: u32) -> Self {
+        let ambient = &scene.ambient;
+        Self {
+            ambient: [ambient.r, ambient.g, ambient.b, 0.0],
+            exposure: scene.exposure,
+            light_count,
+            _padding: [0.0; 2],
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+// The G-buffer attachments are bound with a single generated function
+#![allow(clippy::too_many_arguments)]
+
+use crate::*;
+use rayca_pipe::*;
+
+pipewriter!(
+    Lighting,
+    "shaders/lighting.vert.slang",
+    "shaders/lighting.frag.slang"
+);
+
+/// Deferred lighting resolve, which shades the G-buffer written by the geometry subpass
+/// with the lights gathered by the frame and tonemaps the result into the present attachment.
+/// World positions are reconstructed from depth with the inverse view-projection uploaded by the frame.
+/// Lights casting shadows sample the atlas rendered by `Frame::render_shadows`, if any.
+impl RenderPipeline for PipelineLighting {
+    fn render(
+        &self,
+        frame: &mut Frame,
+        _scene: &RenderScene,
+        camera_infos: &[CameraDrawInfo],
+        _infos: Vec<DrawInfo>,
+    ) {
+        self.bind(&frame.cache);
+
+        let key = DescriptorKey::builder()
+            .layout(self.get_layout())
+            .view(frame.buffer.get_view(COLOR_ATTACHMENT).unwrap().view)
+            .build();
+        let color_texture = RenderTexture::new(
+            frame.buffer.get_view(COLOR_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let normal_texture = RenderTexture::new(
+            frame.buffer.get_view(NORMAL_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let depth_texture = RenderTexture::new(
+            frame.buffer.get_view(DEPTH_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let material_texture = RenderTexture::new(
+            frame.buffer.get_view(MATERIAL_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let emission_texture = RenderTexture::new(
+            frame.buffer.get_view(EMISSION_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        self.bind_color_and_normal_and_depth_and_material_and_emission(
+            &frame.cache.command_buffer,
+            &mut frame.cache.descriptors,
+            key,
+            &color_texture,
+            &normal_texture,
+            &depth_texture,
+            &material_texture,
+            &emission_texture,
+        );
+
+        // The light buffer is recreated when it grows
+        let key = DescriptorKey::builder()
+            .layout(self.get_layout())
+            .buffer(frame.cache.light_buffer.buffer)
+            .build();
+        self.bind_lighting_and_lights(
+            &frame.cache.command_buffer,
+            &mut frame.cache.descriptors,
+            key,
+            &frame.cache.lighting_buffer,
+            &frame.cache.light_buffer,
+        );
+
+        // Frames which did not render shadows sample the empty fallback atlas
+        let fallback = &frame.dev.fallback;
+        let (shadow_buffer, shadow_texture) = match frame.cache.shadow_atlas.as_ref() {
+            Some(atlas) if !frame.shadows.is_empty() => {
+                (&frame.cache.shadow_buffer, atlas.get_texture())
+            }
+            _ => (&fallback.shadow_buffer, fallback.shadow_texture),
+        };
+        let key = DescriptorKey::builder()
+            .layout(self.get_layout())
+            .buffer(shadow_buffer.buffer)
+            .view(shadow_texture.view)
+            .build();
+        self.bind_shadows_and_shadow_atlas(
+            &frame.cache.command_buffer,
+            &mut frame.cache.descriptors,
+            key,
+            shadow_buffer,
+            &shadow_texture,
+        );
+
+        for camera_info in camera_infos {
+            let camera_key = ModelMatrixKey {
+                model: camera_info.model,
+                node: camera_info.node,
+            };
+            let inverse_view_proj_key = InverseViewProjKey {
+                model: camera_info.model,
+                node: camera_info.node,
+                view: frame.current_view,
+            };
+            let inverse_view_proj_buffer = frame
+                .cache
+                .inverse_view_proj_buffers
+                .get(&inverse_view_proj_key)
+                .unwrap();
+            let key = DescriptorKey::builder()
+                .layout(self.get_layout())
+                .model(camera_info.model)
+                .node(camera_info.node)
+                .camera(camera_info.camera)
+                .buffer(inverse_view_proj_buffer.buffer)
+                .build();
+            self.bind_camera_and_inverse_view_proj(
+                &frame.cache.command_buffer,
+                &mut frame.cache.descriptors,
+                key,
+                frame.cache.model_buffers.get(&camera_key).unwrap(),
+                inverse_view_proj_buffer,
+            );
+
+            self.draw(&frame.cache, &frame.dev.fallback.present_primitive);
+        }
+    }
+}
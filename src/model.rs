@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use ash::vk;
-use std::{mem::*, sync::Arc};
+use std::{collections::HashMap, mem::*, path::Path, sync::Arc, sync::mpsc};
 
 use crate::*;
 
@@ -32,8 +32,9 @@ pub trait VertexInput {
                 .src_color_blend_factor(vk::BlendFactor::ONE)
                 .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA),
         ];
+        // The geometry subpass writes color, normal, material, and emission
         if Self::get_subpass() == 0 {
-            ret.push(ret[0]);
+            ret.resize(4, ret[0]);
         }
         ret
     }
@@ -182,9 +183,16 @@ pub struct RenderModel {
     pub buffer_views: Pack<RenderBufferView>,
     pub images: Pack<RenderImage>,
     pub views: Pack<ImageView>,
-    pub samplers: Pack<RenderSampler>,
+    pub samplers: Pack<Arc<RenderSampler>>,
     pub textures: Pack<RenderTexture>,
     pub primitives: Pack<RenderPrimitive>,
+    pub lights: Pack<PunctualLight>,
+    /// Lights attached to nodes, as glTF nodes do not know about them
+    node_lights: HashMap<Handle<Node>, Handle<PunctualLight>>,
+    /// Projections of cameras set with `set_projection`, which replace their projection matrices
+    projections: HashMap<Handle<Camera>, CameraProjection>,
+    /// Normal scale and occlusion strength of materials, which rayca-gltf does not keep
+    material_scales: HashMap<Handle<Material>, MaterialScales>,
 
     /// Useful for constructing the model continuously
     dev: Arc<Dev>,
@@ -201,6 +209,10 @@ impl RenderModel {
             samplers: Pack::new(),
             textures: Pack::new(),
             primitives: Pack::new(),
+            lights: Pack::new(),
+            node_lights: HashMap::new(),
+            projections: HashMap::new(),
+            material_scales: HashMap::new(),
             dev: dev.clone(),
         }
     }
@@ -221,8 +233,79 @@ impl RenderModel {
         ret
     }
 
-    pub fn new_with_gltf(dev: &Arc<Dev>, assets: &Assets, gltf: Model) -> Self {
+    /// Creates a render model from a glTF model and the parts of its document rayca-gltf
+    /// does not keep, such as punctual lights. See `load_gltf_path` for reading both.
+    pub fn new_with_gltf(
+        dev: &Arc<Dev>,
+        assets: &Assets,
+        gltf: Model,
+        extensions: GltfExtensions,
+    ) -> Self {
+        Self::try_new_with_gltf(dev, assets, gltf, extensions).expect("Failed to load glTF model")
+    }
+
+    pub fn try_new_with_gltf(
+        dev: &Arc<Dev>,
+        assets: &Assets,
+        gltf: Model,
+        extensions: GltfExtensions,
+    ) -> Result<Self> {
+        let (model, mut token) = Self::try_new_with_gltf_async(dev, assets, gltf, extensions)?;
+        token.wait();
+        Ok(model)
+    }
+
+    /// Creates a render model whose images are uploaded with a single transfer submission.
+    /// The model should not be rendered until the returned token is ready.
+    /// See `ModelLoader` for decoding images without blocking.
+    pub fn new_with_gltf_async(
+        dev: &Arc<Dev>,
+        assets: &Assets,
+        gltf: Model,
+        extensions: GltfExtensions,
+    ) -> (Self, UploadToken) {
+        Self::try_new_with_gltf_async(dev, assets, gltf, extensions)
+            .expect("Failed to load glTF model")
+    }
+
+    pub fn try_new_with_gltf_async(
+        dev: &Arc<Dev>,
+        assets: &Assets,
+        gltf: Model,
+        extensions: GltfExtensions,
+    ) -> Result<(Self, UploadToken)> {
+        ModelLoader::new(dev, assets, gltf, extensions).try_finish()
+    }
+
+    /// Loads the glTF file at `path` along with the extensions rayca-gltf does not read,
+    /// such as `KHR_texture_basisu` and `KHR_lights_punctual`
+    pub fn load_gltf_path<P: AsRef<Path>>(dev: &Arc<Dev>, assets: &Assets, path: P) -> Self {
+        Self::try_load_gltf_path(dev, assets, path).expect("Failed to load glTF model")
+    }
+
+    pub fn try_load_gltf_path<P: AsRef<Path>>(
+        dev: &Arc<Dev>,
+        assets: &Assets,
+        path: P,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let extensions = GltfExtensions::from_slice(&assets.load(path).into_bytes())?;
+        let gltf = Model::load_gltf_path(path, assets)
+            .map_err(|err| Error::Model(format!("{:?}", err)))?;
+        Self::try_new_with_gltf(dev, assets, gltf, extensions)
+    }
+
+    /// Creates a render model with the images of `gltf` already decoded, in the same order
+    fn try_new_with_images(
+        dev: &Arc<Dev>,
+        gltf: Model,
+        images: Vec<ImageData>,
+        extensions: &GltfExtensions,
+    ) -> Result<(Self, UploadToken)> {
         let mut ret = Self::new(dev);
+        for (&material, &scales) in &extensions.material_scales {
+            ret.set_material_scales(material.into(), scales);
+        }
 
         // Load buffers
         for buffer in gltf.buffers.iter() {
@@ -232,17 +315,10 @@ impl RenderModel {
             ret.push_render_buffer_views(buffer_view);
         }
 
-        // Load images concurrently
-        use rayon::iter::*;
-
-        let render_images: Vec<RenderImage> = gltf
-            .images
-            .par_iter()
-            .map(|image| {
-                RenderImage::load(&dev.allocator, &dev.graphics_queue, assets.load(&image.uri))
-            })
-            .collect();
-        for image in render_images {
+        let options = ImageLoadOptions::default().generate_mips(true);
+        let mut uploader = Uploader::new(dev);
+        for image in images {
+            let image = RenderImage::try_from_image_data(&mut uploader, &image, &options)?;
             ret.push_render_image(image);
         }
         for sampler in gltf.samplers.iter() {
@@ -255,8 +331,16 @@ impl RenderModel {
             ret.push_render_primitive(primitive);
         }
 
+        if !extensions.lights.is_empty() {
+            let nodes = extensions.get_node_handles(&gltf)?;
+            for (node, light) in extensions.lights.iter() {
+                let hlight = ret.push_light(light.clone());
+                ret.attach_light(nodes[*node], hlight);
+            }
+        }
+
         ret.gltf = gltf;
-        ret
+        Ok((ret, uploader.submit()))
     }
 
     pub fn push_camera(&mut self, camera: Camera) -> Handle<Camera> {
@@ -298,16 +382,52 @@ impl RenderModel {
         self.views.push(view);
     }
 
+    /// Works out the role of an image from the material textures referencing it.
+    /// Data takes precedence, as gamma-decoding it breaks shading the most.
+    fn get_image_role(gltf: &Model, image: Handle<Image>) -> TextureRole {
+        let is_data = gltf.materials.iter().any(|material| {
+            [
+                material.normal,
+                material.metallic_roughness,
+                material.occlusion,
+            ]
+            .into_iter()
+            .filter_map(|texture| gltf.textures.get(texture))
+            .any(|texture| texture.image == image)
+        });
+        if is_data {
+            TextureRole::Data
+        } else {
+            TextureRole::Color
+        }
+    }
+
+    /// Pushes an image whose role is worked out from the materials pushed so far
     pub fn push_image(&mut self, image: Image, assets: &Assets) -> Handle<Image> {
+        let role = Self::get_image_role(&self.gltf, self.gltf.images.len().into());
+        self.push_image_with_role(image, assets, role)
+    }
+
+    /// Pushes an image overriding its role, so that it gets a UNORM or SRGB format regardless
+    /// of how materials reference it
+    pub fn push_image_with_role(
+        &mut self,
+        image: Image,
+        assets: &Assets,
+        role: TextureRole,
+    ) -> Handle<Image> {
         let image_asset = assets.load(&image.uri);
-        let render_image =
-            RenderImage::load(&self.dev.allocator, &self.dev.graphics_queue, image_asset);
+        let options = ImageLoadOptions::default().generate_mips(true).role(role);
+        let mut uploader = Uploader::new(&self.dev);
+        let render_image = RenderImage::load_with_options(&mut uploader, image_asset, &options);
+        // The image can be used as soon as this returns
+        uploader.submit().wait();
         self.push_render_image(render_image);
         self.gltf.images.push(image)
     }
 
-    fn push_render_sampler(&mut self, _sampler: &Sampler) {
-        let sampler = RenderSampler::new(&self.dev.device.device);
+    fn push_render_sampler(&mut self, sampler: &Sampler) {
+        let sampler = self.dev.sampler_cache.get(&SamplerDesc::from(sampler));
         self.samplers.push(sampler);
     }
 
@@ -319,7 +439,7 @@ impl RenderModel {
     fn push_render_texture(&mut self, texture: &Texture) {
         let view = self.views.get(texture.image.id.into()).unwrap();
         let sampler = match self.samplers.get(texture.sampler.id.into()) {
-            Some(s) => s,
+            Some(s) => s.as_ref(),
             None => &self.dev.fallback.white_sampler,
         };
         let texture = RenderTexture::new(&view, &sampler);
@@ -345,10 +465,49 @@ impl RenderModel {
         self.gltf.primitives.push(primitive)
     }
 
+    /// Sets the normal scale and occlusion strength of `material`
+    pub fn set_material_scales(&mut self, material: Handle<Material>, scales: MaterialScales) {
+        self.material_scales.insert(material, scales);
+    }
+
+    /// Returns the scales of `material`, which do not change its textures when not set
+    pub fn get_material_scales(&self, material: Handle<Material>) -> MaterialScales {
+        self.material_scales
+            .get(&material)
+            .copied()
+            .unwrap_or_default()
+    }
+
     pub fn push_mesh(&mut self, mesh: Mesh) -> Handle<Mesh> {
         self.gltf.meshes.push(mesh)
     }
 
+    pub fn push_light(&mut self, light: PunctualLight) -> Handle<PunctualLight> {
+        self.lights.push(light)
+    }
+
+    /// Attaches a light to a node, which gives it a position and a direction.
+    /// A node can have one light at most, and the same light can be attached to many nodes.
+    pub fn attach_light(&mut self, node: Handle<Node>, light: Handle<PunctualLight>) {
+        self.node_lights.insert(node, light);
+    }
+
+    /// Sets how `camera` projects the scene
+    pub fn set_projection(&mut self, camera: Handle<Camera>, projection: CameraProjection) {
+        self.projections.insert(camera, projection);
+    }
+
+    /// Returns the projection of `camera`. When it has not been set, it is recovered
+    /// from the projection matrix of the camera, falling back to the default one.
+    pub fn get_projection(&self, camera: Handle<Camera>) -> CameraProjection {
+        if let Some(projection) = self.projections.get(&camera) {
+            return *projection;
+        }
+        self.get_camera(camera)
+            .and_then(|camera| CameraProjection::from_gltf_matrix(&camera.projection))
+            .unwrap_or_default()
+    }
+
     pub fn push_script(&mut self, script: Script) -> Handle<Script> {
         self.gltf.scripts.push(script)
     }
@@ -393,6 +552,23 @@ impl RenderModel {
         self.gltf.materials.get_mut(material)
     }
 
+    pub fn get_light(&self, light: Handle<PunctualLight>) -> Option<&PunctualLight> {
+        self.lights.get(light)
+    }
+
+    pub fn get_light_mut(&mut self, light: Handle<PunctualLight>) -> Option<&mut PunctualLight> {
+        self.lights.get_mut(light)
+    }
+
+    /// Returns the light attached to `node`, if any
+    pub fn get_node_light(&self, node: Handle<Node>) -> Option<Handle<PunctualLight>> {
+        self.node_lights.get(&node).copied()
+    }
+
+    pub fn get_texture(&self, texture: Handle<Texture>) -> Option<&RenderTexture> {
+        self.textures.get(texture.id.into())
+    }
+
     pub fn get_root(&self) -> &Node {
         &self.gltf.scene
     }
@@ -420,3 +596,118 @@ impl RenderModel {
         Handle::NONE
     }
 }
+
+/// Loads a glTF model decoding its images on the rayon thread pool, so that the thread
+/// creating it can keep rendering. Vulkan resources are only created by `try_finish`.
+pub struct ModelLoader {
+    gltf: Model,
+    extensions: GltfExtensions,
+    receiver: mpsc::Receiver<Vec<Result<ImageData>>>,
+    images: Option<Vec<Result<ImageData>>>,
+    dev: Arc<Dev>,
+}
+
+impl ModelLoader {
+    /// Reads the images of `gltf` and starts decoding them. KTX2 images, such as the
+    /// sources of `KHR_texture_basisu` textures, keep their compressed format and levels.
+    pub fn new(dev: &Arc<Dev>, assets: &Assets, gltf: Model, extensions: GltfExtensions) -> Self {
+        // Assets are read here, as they may not be shared with other threads
+        let images: Vec<(Vec<u8>, TextureRole)> = gltf
+            .images
+            .iter()
+            .enumerate()
+            .map(|(index, image)| {
+                let role = RenderModel::get_image_role(&gltf, index.into());
+                (assets.load(&image.uri).into_bytes(), role)
+            })
+            .collect();
+
+        let (sender, receiver) = mpsc::channel();
+        let device = dev.device.clone();
+        rayon::spawn(move || {
+            use rayon::iter::*;
+            let images = images
+                .into_par_iter()
+                .map(|(bytes, role)| {
+                    // A decoder panicking on a malformed image only fails that image
+                    let decode = || ImageData::decode_bytes(&device, bytes, role);
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(decode)).unwrap_or_else(
+                        |panic| {
+                            let msg = panic
+                                .downcast_ref::<&str>()
+                                .map(|msg| msg.to_string())
+                                .or_else(|| panic.downcast_ref::<String>().cloned())
+                                .unwrap_or_default();
+                            Err(Error::InvalidImage(format!("Decoder panicked: {}", msg)))
+                        },
+                    )
+                })
+                .collect();
+            // Nobody is waiting when the loader has been dropped
+            let _ = sender.send(images);
+        });
+
+        Self {
+            gltf,
+            extensions,
+            receiver,
+            images: None,
+            dev: dev.clone(),
+        }
+    }
+
+    /// Returns whether all images have been decoded, without blocking
+    pub fn is_decoded(&mut self) -> bool {
+        if self.images.is_none() {
+            self.images = self.receiver.try_recv().ok();
+        }
+        self.images.is_some()
+    }
+
+    /// Waits for the images to be decoded, then creates the render model recording
+    /// the upload of all its images into a single transfer submission.
+    /// The model should not be rendered until the returned token is ready.
+    pub fn try_finish(mut self) -> Result<(RenderModel, UploadToken)> {
+        let images = match self.images.take() {
+            Some(images) => images,
+            // Decoder panics are caught, so the decoded images are always sent
+            None => self
+                .receiver
+                .recv()
+                .expect("Failed to receive decoded images"),
+        };
+
+        // KTX2 sources of `KHR_texture_basisu` textures replace their fallback sources,
+        // unless they could not be decoded, as their format is not supported by the device
+        for (&texture, &source) in &self.extensions.basisu_sources {
+            let Some(texture) = self.gltf.textures.get_mut(texture.into()) else {
+                continue;
+            };
+            let Some(image) = images.get(source) else {
+                continue;
+            };
+            let has_fallback = texture.image.id < images.len() && texture.image.id != source;
+            if image.is_ok() || !has_fallback {
+                texture.image = source.into();
+            }
+        }
+
+        let images = images
+            .into_iter()
+            .enumerate()
+            .map(|(index, image)| {
+                let is_used = self.gltf.textures.iter().any(|t| t.image.id == index);
+                match image {
+                    // Replaced by a fallback, it only needs a placeholder to keep indices
+                    Err(_) if !is_used => Ok(ImageData::from_rgba8(
+                        ::image::RgbaImage::new(1, 1),
+                        TextureRole::Data,
+                    )),
+                    image => image,
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        RenderModel::try_new_with_images(&self.dev, self.gltf, images, &self.extensions)
+    }
+}
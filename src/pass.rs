@@ -8,158 +8,102 @@ use ash::vk;
 
 use crate::*;
 
+/// A Vulkan render pass compiled from a `RenderGraph`
 pub struct Pass {
     pub render: vk::RenderPass,
+    /// Layout of the external attachment at the end of the render pass,
+    /// such as the present attachment, or the depth attachment of shadow passes
+    pub present_layout: vk::ImageLayout,
+    /// Attachments of the graph, in the order expected by framebuffers
+    pub attachments: Vec<PassAttachment>,
+    /// Passes of the graph, in the order of their subpasses
+    pub passes: Vec<PassDesc>,
     pub device: Arc<ash::Device>,
 }
 
 impl Pass {
+    /// Creates a render pass which leaves the present attachment ready to be presented
     pub fn new(dev: &Dev) -> Self {
-        // Render pass (swapchain surface format, device)
-        let present_attachment = vk::AttachmentDescription::default()
-            .format(dev.surface_format.format)
-            .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::CLEAR)
-            .store_op(vk::AttachmentStoreOp::STORE)
-            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
-            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-            .initial_layout(vk::ImageLayout::UNDEFINED)
-            .final_layout(vk::ImageLayout::PRESENT_SRC_KHR);
-
-        let depth_attachment = vk::AttachmentDescription::default()
-            .format(vk::Format::D32_SFLOAT)
-            .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::CLEAR)
-            .store_op(vk::AttachmentStoreOp::DONT_CARE)
-            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
-            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-            .initial_layout(vk::ImageLayout::UNDEFINED)
-            .final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
-
-        let color_attachment = vk::AttachmentDescription::default()
-            // @todo This format should come from a "framebuffer" object
-            .format(dev.surface_format.format)
-            .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::CLEAR)
-            .store_op(vk::AttachmentStoreOp::DONT_CARE)
-            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
-            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-            .initial_layout(vk::ImageLayout::UNDEFINED)
-            .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
-
-        let normal_attachment = vk::AttachmentDescription::default()
-            .format(vk::Format::A2R10G10B10_UNORM_PACK32)
-            .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::CLEAR)
-            .store_op(vk::AttachmentStoreOp::DONT_CARE)
-            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
-            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-            .initial_layout(vk::ImageLayout::UNDEFINED)
-            .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
-
-        let attachments = [
-            present_attachment,
-            depth_attachment,
-            color_attachment,
-            normal_attachment,
-        ];
-
-        let present_ref = vk::AttachmentReference::default()
-            .attachment(0)
-            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
-
-        let depth_ref = vk::AttachmentReference::default()
-            .attachment(1)
-            .layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
-
-        let color_ref = vk::AttachmentReference::default()
-            .attachment(2)
-            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
-
-        let normal_ref = vk::AttachmentReference::default()
-            .attachment(3)
-            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
-
-        let present_refs = [present_ref];
-        let color_refs = [color_ref, normal_ref];
-
-        let color_input_ref = vk::AttachmentReference::default()
-            .attachment(2)
-            .layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        RenderGraph::deferred(
+            dev.surface_format.format,
+            vk::ImageLayout::PRESENT_SRC_KHR,
+            false,
+        )
+        .compile(dev)
+    }
 
-        let normal_input_ref = ash::vk::AttachmentReference::default()
-            .attachment(3)
-            .layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+    /// Creates a render pass which leaves the present attachment ready to be transferred,
+    /// useful when rendering to offscreen images without a surface or a swapchain.
+    /// Depth, color, and normal attachments are stored as well, so they can be read back.
+    pub fn offscreen(dev: &Dev) -> Self {
+        RenderGraph::deferred(
+            dev.surface_format.format,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            true,
+        )
+        .compile(dev)
+    }
 
-        let depth_input_ref = ash::vk::AttachmentReference::default()
-            .attachment(1)
-            .layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+    /// Creates a depth-only render pass for shadow maps, which are left ready to be sampled
+    pub fn shadow(dev: &Dev) -> Self {
+        RenderGraph::new()
+            .attachment(
+                AttachmentDesc::depth("shadow", SHADOW_FORMAT, 0.0)
+                    .external()
+                    .final_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL),
+            )
+            .pass(PassDesc::new("shadow").depth("shadow"))
+            .compile(dev)
+    }
 
-        let input_refs = [color_input_ref, normal_input_ref, depth_input_ref];
+    pub fn get_attachment(&self, name: &str) -> Option<&PassAttachment> {
+        self.attachments
+            .iter()
+            .find(|attachment| attachment.desc.name == name)
+    }
 
-        // Two subpasses
-        let subpasses = [
-            vk::SubpassDescription::default()
-                .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-                .color_attachments(&color_refs)
-                .depth_stencil_attachment(&depth_ref),
-            vk::SubpassDescription::default()
-                .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-                .color_attachments(&present_refs)
-                .input_attachments(&input_refs),
-        ];
+    /// Returns the clear values of the attachments, in the order expected by `vkCmdBeginRenderPass`.
+    /// Attachments cleared to the background are cleared to `background` instead, if any.
+    pub fn get_clear_values(&self, background: Option<Color>) -> Vec<vk::ClearValue> {
+        self.attachments
+            .iter()
+            .map(|attachment| attachment.desc.get_clear_value(background))
+            .collect()
+    }
 
-        // These dependencies follow the example from
-        // https://github.com/SaschaWillems/Vulkan/blob/master/examples/subpasses/subpasses.cpp
-        let init_dependency = vk::SubpassDependency::default()
-            .src_subpass(vk::SUBPASS_EXTERNAL)
-            .dst_subpass(0)
-            .src_stage_mask(vk::PipelineStageFlags::BOTTOM_OF_PIPE)
-            .src_access_mask(vk::AccessFlags::MEMORY_READ)
-            .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-            .dst_access_mask(
-                vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+    /// Returns how `vkCmdClearAttachments` clears the attachments written by `subpass`
+    /// the way the render pass clears them, for clearing parts of them again
+    pub fn get_clear_attachments(
+        &self,
+        subpass: u32,
+        background: Option<Color>,
+    ) -> Vec<vk::ClearAttachment> {
+        let Some(pass) = self.passes.get(subpass as usize) else {
+            return vec![];
+        };
+        let get_desc = |name: &String| {
+            self.get_attachment(name)
+                .map(|attachment| &attachment.desc)
+                .filter(|desc| desc.clear.is_some())
+        };
+        let colors = pass.colors.iter().enumerate().filter_map(|(index, name)| {
+            let desc = get_desc(name)?;
+            Some(
+                vk::ClearAttachment::default()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .color_attachment(index as u32)
+                    .clear_value(desc.get_clear_value(background)),
             )
-            .dependency_flags(vk::DependencyFlags::BY_REGION);
-
-        let output_to_input_dependency = vk::SubpassDependency::default()
-            .src_subpass(0)
-            .dst_subpass(1)
-            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
-            .dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
-            .dst_access_mask(vk::AccessFlags::INPUT_ATTACHMENT_READ)
-            .dependency_flags(vk::DependencyFlags::BY_REGION);
-
-        let present_dependency = vk::SubpassDependency::default()
-            .src_subpass(1)
-            .dst_subpass(vk::SUBPASS_EXTERNAL)
-            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-            .src_access_mask(
-                vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+        });
+        let depth = pass.depth.iter().filter_map(|name| {
+            let desc = get_desc(name)?;
+            Some(
+                vk::ClearAttachment::default()
+                    .aspect_mask(vk::ImageAspectFlags::DEPTH)
+                    .clear_value(desc.get_clear_value(background)),
             )
-            .dst_stage_mask(vk::PipelineStageFlags::BOTTOM_OF_PIPE)
-            .dst_access_mask(vk::AccessFlags::MEMORY_READ)
-            .dependency_flags(vk::DependencyFlags::BY_REGION);
-
-        let dependencies = [
-            init_dependency,
-            output_to_input_dependency,
-            present_dependency,
-        ];
-
-        // Build the render pass
-        let create_info = vk::RenderPassCreateInfo::default()
-            .attachments(&attachments)
-            .subpasses(&subpasses)
-            .dependencies(&dependencies);
-        let render = unsafe { dev.device.create_render_pass(&create_info, None) }
-            .expect("Failed to create Vulkan render pass");
-
-        Self {
-            render,
-            device: dev.device.device.clone(),
-        }
+        });
+        colors.chain(depth).collect()
     }
 }
 
@@ -0,0 +1,173 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+// Material textures and factors are bound with a single generated function
+#![allow(clippy::too_many_arguments)]
+
+use crate::*;
+use rayca_pipe::*;
+
+pipewriter!(Pbr, "shaders/pbr.vert.slang", "shaders/pbr.frag.slang");
+
+/// Factors of the normal and occlusion textures of a glTF material
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MaterialScales {
+    /// Scales the X and Y components of the normal texture
+    pub normal_scale: f32,
+    /// From 0 ignoring the occlusion texture, to 1 applying it fully
+    pub occlusion_strength: f32,
+}
+
+impl Default for MaterialScales {
+    fn default() -> Self {
+        Self {
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+        }
+    }
+}
+
+/// Material factors as laid out in the uniform buffer of the PBR pipeline
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PbrMaterial {
+    pub base_color: Color,
+    /// The last component is not used
+    pub emissive: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Zero when there is no normal texture, so that the white fallback has no effect
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+}
+
+impl PbrMaterial {
+    pub fn new(material: &Material, scales: &MaterialScales, has_normal_texture: bool) -> Self {
+        let emissive = &material.emissive_factor;
+        Self {
+            base_color: material.color,
+            emissive: [emissive.x, emissive.y, emissive.z, 0.0],
+            metallic: material.metallic_factor,
+            roughness: material.roughness_factor,
+            normal_scale: if has_normal_texture {
+                scales.normal_scale
+            } else {
+                0.0
+            },
+            occlusion_strength: scales.occlusion_strength,
+        }
+    }
+}
+
+/// Writes the factors and the textures of glTF metallic-roughness materials into the
+/// G-buffer, to be shaded by `PipelineLighting`. Missing textures are replaced by white.
+impl RenderPipeline for PipelinePbr {
+    fn render(
+        &self,
+        frame: &mut Frame,
+        scene: &RenderScene,
+        camera_infos: &[CameraDrawInfo],
+        infos: Vec<DrawInfo>,
+    ) {
+        self.bind(&frame.cache);
+
+        for camera_info in camera_infos {
+            let view_key = ViewMatrixKey {
+                model: camera_info.model,
+                node: camera_info.node,
+            };
+            let proj_key = ProjMatrixKey {
+                model: camera_info.model,
+                camera: camera_info.camera,
+                view: frame.current_view,
+            };
+            let proj_buffer = frame.cache.proj_buffers.get(&proj_key).unwrap();
+            let key = DescriptorKey::builder()
+                .layout(self.get_layout())
+                .model(camera_info.model)
+                .node(camera_info.node)
+                .camera(camera_info.camera)
+                .buffer(proj_buffer.buffer)
+                .build();
+            self.bind_view_and_proj(
+                &frame.cache.command_buffer,
+                &mut frame.cache.descriptors,
+                key,
+                frame.cache.view_buffers.get(&view_key).unwrap(),
+                proj_buffer,
+            );
+
+            for info in infos.iter() {
+                let model = scene.get_model(info.model).unwrap();
+
+                let model_key = ModelMatrixKey {
+                    model: info.model,
+                    node: info.node,
+                };
+                let normal_key = NormalMatrixKey {
+                    model: info.model,
+                    node: info.node,
+                    view: Handle::NONE,
+                };
+                let key = DescriptorKey::builder()
+                    .layout(self.get_layout())
+                    .model(info.model)
+                    .node(info.node)
+                    .build();
+                self.bind_model_and_normal_matrix(
+                    &frame.cache.command_buffer,
+                    &mut frame.cache.descriptors,
+                    key,
+                    frame.cache.model_buffers.get(&model_key).unwrap(),
+                    frame.cache.normal_buffers.get(&normal_key).unwrap(),
+                );
+
+                let primitive = model.get_primitive(info.primitive).unwrap();
+                let material = model
+                    .get_material(primitive.material)
+                    .unwrap_or(&frame.dev.fallback.white_material);
+                let material_key = MaterialKey {
+                    model: info.model,
+                    material: primitive.material,
+                };
+                let material_buffer = frame
+                    .cache
+                    .pbr_material_buffers
+                    .get(&material_key)
+                    .unwrap_or(&frame.dev.fallback.pbr_material_buffer);
+                let [albedo, metallic_roughness, normal, occlusion, emissive] = [
+                    material.albedo,
+                    material.metallic_roughness,
+                    material.normal,
+                    material.occlusion,
+                    material.emissive,
+                ]
+                .map(|texture| {
+                    model
+                        .get_texture(texture)
+                        .unwrap_or(&frame.dev.fallback.white_texture)
+                });
+                let key = DescriptorKey::builder()
+                    .layout(self.get_layout())
+                    .model(info.model)
+                    .material(primitive.material)
+                    .build();
+                self.bind_material_and_albedo_and_metallic_roughness_and_normal_map_and_occlusion_and_emissive(
+                    &frame.cache.command_buffer,
+                    &mut frame.cache.descriptors,
+                    key,
+                    material_buffer,
+                    albedo,
+                    metallic_roughness,
+                    normal,
+                    occlusion,
+                    emissive,
+                );
+
+                let render_primitive = model.primitives.get(info.primitive.id.into()).unwrap();
+                self.draw(&frame.cache, render_primitive);
+            }
+        }
+    }
+}
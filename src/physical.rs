@@ -0,0 +1,224 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use std::{ffi::CStr, sync::Arc};
+
+use ash::vk;
+
+use crate::*;
+
+/// Environment variable which overrides the device chosen by the selector of the context,
+/// among the devices meeting its requirements. A number selects a device by index, anything else by name.
+pub const DEVICE_ENV_VAR: &str = "RAYCA_DEVICE";
+
+/// Properties of a physical device, useful for choosing which one to use
+#[derive(Clone)]
+pub struct PhysicalDeviceInfo {
+    /// Index in the list of physical devices enumerated by the instance
+    pub index: usize,
+    pub physical: vk::PhysicalDevice,
+    pub properties: vk::PhysicalDeviceProperties,
+    pub features: vk::PhysicalDeviceFeatures,
+    pub queue_families: Vec<vk::QueueFamilyProperties>,
+    pub extensions: Vec<String>,
+}
+
+impl PhysicalDeviceInfo {
+    pub fn new(
+        instance: &ash::Instance,
+        index: usize,
+        physical: vk::PhysicalDevice,
+    ) -> Result<Self> {
+        let properties = unsafe { instance.get_physical_device_properties(physical) };
+        let features = unsafe { instance.get_physical_device_features(physical) };
+        let queue_families =
+            unsafe { instance.get_physical_device_queue_family_properties(physical) };
+        let extensions = unsafe { instance.enumerate_device_extension_properties(physical) }?
+            .iter()
+            .filter_map(|extension| extension.extension_name_as_c_str().ok())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect();
+
+        Ok(Self {
+            index,
+            physical,
+            properties,
+            features,
+            queue_families,
+            extensions,
+        })
+    }
+
+    /// Returns information about all the physical devices available to this instance
+    pub fn enumerate(instance: &ash::Instance) -> Result<Vec<Self>> {
+        let physicals = unsafe { instance.enumerate_physical_devices() }?;
+        physicals
+            .into_iter()
+            .enumerate()
+            .map(|(index, physical)| Self::new(instance, index, physical))
+            .collect()
+    }
+
+    pub fn get_name(&self) -> String {
+        self.properties
+            .device_name_as_c_str()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    pub fn get_type(&self) -> vk::PhysicalDeviceType {
+        self.properties.device_type
+    }
+
+    pub fn supports_extension(&self, name: &CStr) -> bool {
+        let name = name.to_string_lossy();
+        self.extensions.iter().any(|extension| *extension == name)
+    }
+
+    /// Returns whether all the features enabled in `required` are supported
+    pub fn supports_features(&self, required: &vk::PhysicalDeviceFeatures) -> bool {
+        let available = Self::features_as_slice(&self.features);
+        let required = Self::features_as_slice(required);
+        available
+            .iter()
+            .zip(required)
+            .all(|(available, required)| *required == vk::FALSE || *available == vk::TRUE)
+    }
+
+    fn features_as_slice(features: &vk::PhysicalDeviceFeatures) -> &[vk::Bool32] {
+        // Physical device features is a plain list of booleans
+        unsafe {
+            std::slice::from_raw_parts(
+                features as *const vk::PhysicalDeviceFeatures as *const vk::Bool32,
+                size_of::<vk::PhysicalDeviceFeatures>() / size_of::<vk::Bool32>(),
+            )
+        }
+    }
+
+    /// Higher scores for device types which are usually faster
+    pub fn get_type_score(&self) -> u32 {
+        match self.get_type() {
+            vk::PhysicalDeviceType::DISCRETE_GPU => 4,
+            vk::PhysicalDeviceType::INTEGRATED_GPU => 3,
+            vk::PhysicalDeviceType::VIRTUAL_GPU => 2,
+            vk::PhysicalDeviceType::CPU => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// Custom scoring function, where `None` means the device is not suitable
+pub type DeviceScoreFn = Arc<dyn Fn(&PhysicalDeviceInfo) -> Option<u32> + Send + Sync>;
+
+/// Policy for choosing which physical device to use among suitable candidates
+#[derive(Clone, Default)]
+pub enum DeviceSelector {
+    /// Prefers discrete GPUs, then integrated, virtual and CPU devices
+    #[default]
+    PreferDiscrete,
+
+    /// Device at this index in the list of enumerated physical devices
+    Index(usize),
+
+    /// First device whose name contains this string, ignoring case
+    Name(String),
+
+    /// Prefers discrete GPUs among devices supporting these features and extensions,
+    /// which are going to be enabled on the logical device
+    Requirements {
+        features: Box<vk::PhysicalDeviceFeatures>,
+        extensions: Vec<&'static CStr>,
+    },
+
+    /// The device with the highest score
+    Custom(DeviceScoreFn),
+}
+
+impl DeviceSelector {
+    pub fn requirements(
+        features: vk::PhysicalDeviceFeatures,
+        extensions: Vec<&'static CStr>,
+    ) -> Self {
+        Self::Requirements {
+            features: Box::new(features),
+            extensions,
+        }
+    }
+
+    pub fn custom<F>(score: F) -> Self
+    where
+        F: Fn(&PhysicalDeviceInfo) -> Option<u32> + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(score))
+    }
+
+    /// Returns the selector specified by the `RAYCA_DEVICE` environment variable, if any
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(DEVICE_ENV_VAR).ok()?;
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        Some(match value.parse::<usize>() {
+            Ok(index) => Self::Index(index),
+            Err(_) => Self::Name(value.to_string()),
+        })
+    }
+
+    fn score(&self, info: &PhysicalDeviceInfo) -> Option<u32> {
+        match self {
+            Self::PreferDiscrete => Some(info.get_type_score()),
+            Self::Index(index) => (info.index == *index).then_some(0),
+            Self::Name(name) => info
+                .get_name()
+                .to_lowercase()
+                .contains(&name.to_lowercase())
+                .then_some(0),
+            Self::Requirements { .. } => {
+                self.meets_requirements(info).then(|| info.get_type_score())
+            }
+            Self::Custom(score) => score(info),
+        }
+    }
+
+    /// Returns whether the device supports the features and extensions required by this selector,
+    /// which is always the case for selectors without requirements
+    pub fn meets_requirements(&self, info: &PhysicalDeviceInfo) -> bool {
+        info.supports_features(&self.get_required_features())
+            && self
+                .get_required_extensions()
+                .iter()
+                .all(|extension| info.supports_extension(extension))
+    }
+
+    /// Returns the candidate with the highest score, preferring
+    /// the first enumerated one in case of equal scores
+    pub fn select<'c>(
+        &self,
+        candidates: &'c [PhysicalDeviceInfo],
+    ) -> Option<&'c PhysicalDeviceInfo> {
+        candidates
+            .iter()
+            .filter_map(|info| self.score(info).map(|score| (score, info)))
+            .rev()
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, info)| info)
+    }
+
+    /// Features which should be enabled on the logical device
+    pub fn get_required_features(&self) -> vk::PhysicalDeviceFeatures {
+        match self {
+            Self::Requirements { features, .. } => **features,
+            _ => vk::PhysicalDeviceFeatures::default(),
+        }
+    }
+
+    /// Extensions which should be enabled on the logical device
+    pub fn get_required_extensions(&self) -> &[&'static CStr] {
+        match self {
+            Self::Requirements { extensions, .. } => extensions,
+            _ => &[],
+        }
+    }
+}
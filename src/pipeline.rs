@@ -84,17 +84,22 @@ impl RenderPipeline for PipelinePresent {
     ) {
         self.bind(&frame.cache);
 
-        let color_view_handle = vk::Handle::as_raw(frame.buffer.color_view.view);
         let key = DescriptorKey::builder()
             .layout(self.get_layout())
-            .node(Handle::new(color_view_handle as _))
+            .view(frame.buffer.get_view(COLOR_ATTACHMENT).unwrap().view)
             .build();
-        let color_texture =
-            RenderTexture::new(&frame.buffer.color_view, &frame.dev.fallback.white_sampler);
-        let normal_texture =
-            RenderTexture::new(&frame.buffer.normal_view, &frame.dev.fallback.white_sampler);
-        let depth_texture =
-            RenderTexture::new(&frame.buffer.depth_view, &frame.dev.fallback.white_sampler);
+        let color_texture = RenderTexture::new(
+            frame.buffer.get_view(COLOR_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let normal_texture = RenderTexture::new(
+            frame.buffer.get_view(NORMAL_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let depth_texture = RenderTexture::new(
+            frame.buffer.get_view(DEPTH_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
         self.bind_color_and_normal_and_depth(
             &frame.cache.command_buffer,
             &mut frame.cache.descriptors,
@@ -117,17 +122,22 @@ impl RenderPipeline for PipelineNormal {
     ) {
         self.bind(&frame.cache);
 
-        let color_view_handle = vk::Handle::as_raw(frame.buffer.color_view.view);
         let key = DescriptorKey::builder()
             .layout(self.get_layout())
-            .node(Handle::new(color_view_handle as _))
+            .view(frame.buffer.get_view(COLOR_ATTACHMENT).unwrap().view)
             .build();
-        let color_texture =
-            RenderTexture::new(&frame.buffer.color_view, &frame.dev.fallback.white_sampler);
-        let normal_texture =
-            RenderTexture::new(&frame.buffer.normal_view, &frame.dev.fallback.white_sampler);
-        let depth_texture =
-            RenderTexture::new(&frame.buffer.depth_view, &frame.dev.fallback.white_sampler);
+        let color_texture = RenderTexture::new(
+            frame.buffer.get_view(COLOR_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let normal_texture = RenderTexture::new(
+            frame.buffer.get_view(NORMAL_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let depth_texture = RenderTexture::new(
+            frame.buffer.get_view(DEPTH_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
         self.bind_color_and_normal_and_depth(
             &frame.cache.command_buffer,
             &mut frame.cache.descriptors,
@@ -150,17 +160,22 @@ impl RenderPipeline for PipelineDepth {
     ) {
         self.bind(&frame.cache);
 
-        let color_view_handle = vk::Handle::as_raw(frame.buffer.color_view.view);
         let key = DescriptorKey::builder()
             .layout(self.get_layout())
-            .node(Handle::new(color_view_handle as _))
+            .view(frame.buffer.get_view(COLOR_ATTACHMENT).unwrap().view)
             .build();
-        let color_texture =
-            RenderTexture::new(&frame.buffer.color_view, &frame.dev.fallback.white_sampler);
-        let normal_texture =
-            RenderTexture::new(&frame.buffer.normal_view, &frame.dev.fallback.white_sampler);
-        let depth_texture =
-            RenderTexture::new(&frame.buffer.depth_view, &frame.dev.fallback.white_sampler);
+        let color_texture = RenderTexture::new(
+            frame.buffer.get_view(COLOR_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let normal_texture = RenderTexture::new(
+            frame.buffer.get_view(NORMAL_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
+        let depth_texture = RenderTexture::new(
+            frame.buffer.get_view(DEPTH_ATTACHMENT).unwrap(),
+            &frame.dev.fallback.white_sampler,
+        );
         self.bind_color_and_normal_and_depth(
             &frame.cache.command_buffer,
             &mut frame.cache.descriptors,
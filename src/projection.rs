@@ -0,0 +1,171 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use crate::*;
+
+fn to_mat4(rows: [[f32; 4]; 4]) -> Mat4 {
+    // Matrices are uploaded to shaders as they are, with the same layout
+    const _: () = assert!(size_of::<Mat4>() == size_of::<[[f32; 4]; 4]>());
+    unsafe { std::mem::transmute_copy(&rows) }
+}
+
+fn from_mat4(matrix: &Mat4) -> [[f32; 4]; 4] {
+    unsafe { std::mem::transmute_copy(matrix) }
+}
+
+/// How a camera projects the scene. Matrices follow the reverse-Z convention of the renderer,
+/// mapping the near plane to depth 1 and the far plane, or infinity, to depth 0,
+/// which matches depth cleared to 0 and tested with `CompareOp::GREATER`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraProjection {
+    /// Vertical field of view in radians, with the horizontal one following the viewport.
+    /// A `far` of `None` means an infinite far plane.
+    Perspective {
+        yfov: f32,
+        near: f32,
+        far: Option<f32>,
+    },
+
+    /// Half the height of the view volume, with its width following the viewport
+    Orthographic { ymag: f32, near: f32, far: f32 },
+}
+
+impl Default for CameraProjection {
+    fn default() -> Self {
+        Self::perspective(std::f32::consts::FRAC_PI_3, 0.1)
+    }
+}
+
+impl CameraProjection {
+    /// Creates a perspective projection with an infinite far plane
+    pub fn perspective(yfov: f32, near: f32) -> Self {
+        Self::Perspective {
+            yfov,
+            near,
+            far: None,
+        }
+    }
+
+    pub fn orthographic(ymag: f32, near: f32, far: f32) -> Self {
+        Self::Orthographic { ymag, near, far }
+    }
+
+    /// Limits a perspective projection to `far`, or moves the far plane of an orthographic one
+    pub fn far(mut self, far: f32) -> Self {
+        match &mut self {
+            Self::Perspective { far: value, .. } => *value = Some(far),
+            Self::Orthographic { far: value, .. } => *value = far,
+        }
+        self
+    }
+
+    /// Returns the rows of the projection matrix for a viewport of `aspect` width over height
+    fn get_rows(&self, aspect: f32) -> [[f32; 4]; 4] {
+        match *self {
+            Self::Perspective { yfov, near, far } => {
+                let f = 1.0 / (yfov * 0.5).tan();
+                // Depth is `near / distance` when infinite
+                let (a, b) = match far {
+                    Some(far) => (near / (far - near), near * far / (far - near)),
+                    None => (0.0, near),
+                };
+                [
+                    [f / aspect, 0.0, 0.0, 0.0],
+                    [0.0, f, 0.0, 0.0],
+                    [0.0, 0.0, a, b],
+                    [0.0, 0.0, -1.0, 0.0],
+                ]
+            }
+            Self::Orthographic { ymag, near, far } => [
+                [1.0 / (ymag * aspect), 0.0, 0.0, 0.0],
+                [0.0, 1.0 / ymag, 0.0, 0.0],
+                [0.0, 0.0, 1.0 / (far - near), far / (far - near)],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Returns the rows of the inverse of the projection matrix, mapping normalized device
+    /// coordinates back to view space, for a viewport of `aspect` width over height
+    fn get_inverse_rows(&self, aspect: f32) -> [[f32; 4]; 4] {
+        match *self {
+            Self::Perspective { yfov, near, far } => {
+                let f = 1.0 / (yfov * 0.5).tan();
+                let (a, b) = match far {
+                    Some(far) => (near / (far - near), near * far / (far - near)),
+                    None => (0.0, near),
+                };
+                [
+                    [aspect / f, 0.0, 0.0, 0.0],
+                    [0.0, 1.0 / f, 0.0, 0.0],
+                    [0.0, 0.0, 0.0, -1.0],
+                    [0.0, 0.0, 1.0 / b, a / b],
+                ]
+            }
+            Self::Orthographic { ymag, near, far } => [
+                [ymag * aspect, 0.0, 0.0, 0.0],
+                [0.0, ymag, 0.0, 0.0],
+                [0.0, 0.0, far - near, -far],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Returns half the width and height of the view volume at `distance` from the camera,
+    /// for a viewport of `aspect` width over height
+    pub fn get_half_extent(&self, aspect: f32, distance: f32) -> (f32, f32) {
+        let height = match *self {
+            Self::Perspective { yfov, .. } => distance * (yfov * 0.5).tan(),
+            Self::Orthographic { ymag, .. } => ymag,
+        };
+        (height * aspect, height)
+    }
+
+    /// Returns the projection matrix for a viewport of `aspect` width over height
+    pub fn get_matrix(&self, aspect: f32) -> Mat4 {
+        to_mat4(self.get_rows(aspect))
+    }
+
+    /// Returns the inverse of the projection matrix for a viewport of `aspect` width over height
+    pub fn get_inverse_matrix(&self, aspect: f32) -> Mat4 {
+        to_mat4(self.get_inverse_rows(aspect))
+    }
+
+    /// Recovers the parameters of a projection matrix built as glTF cameras define it,
+    /// such as the one of a `Camera`, whether it is stored by rows or by columns.
+    /// Returns `None` for matrices which are not glTF projections.
+    pub fn from_gltf_matrix(matrix: &Mat4) -> Option<Self> {
+        let m = from_mat4(matrix);
+        // Either of them is zero, or -1 for perspective projections, depending on the layout
+        let a = m[2][2];
+        let off_diagonal = m[2][3] + m[3][2];
+        let projection = if m[3][3] == 0.0 {
+            let b = off_diagonal + 1.0;
+            let yfov = 2.0 * (1.0 / m[1][1]).atan();
+            if a == -1.0 {
+                Self::perspective(yfov, -b / 2.0)
+            } else {
+                Self::perspective(yfov, b / (a - 1.0)).far(b / (a + 1.0))
+            }
+        } else {
+            let b = off_diagonal;
+            Self::orthographic(1.0 / m[1][1], (b + 1.0) / a, (b - 1.0) / a)
+        };
+        projection.is_valid().then_some(projection)
+    }
+
+    fn is_valid(&self) -> bool {
+        match *self {
+            Self::Perspective { yfov, near, far } => {
+                yfov > 0.0
+                    && yfov < std::f32::consts::PI
+                    && near > 0.0
+                    && far.is_none_or(|far| far > near && far.is_finite())
+            }
+            Self::Orthographic { ymag, near, far } => {
+                ymag > 0.0 && ymag.is_finite() && near >= 0.0 && far > near && far.is_finite()
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@ use std::sync::Arc;
 
 use crate::*;
 
+#[derive(Clone)]
 pub struct Queue {
     pub queue: vk::Queue,
     device: Arc<ash::Device>,
@@ -13,7 +14,11 @@ pub struct Queue {
 
 impl Queue {
     pub fn new(device: &Device) -> Self {
-        let queue = unsafe { device.get_device_queue(device.graphics_queue_index, 0) };
+        Self::new_with_family(device, device.graphics_queue_index)
+    }
+
+    pub fn new_with_family(device: &Device, family_index: u32) -> Self {
+        let queue = unsafe { device.get_device_queue(family_index, 0) };
         Queue {
             queue,
             device: device.device.clone(),
@@ -121,3 +126,41 @@ impl std::ops::DerefMut for GraphicsQueue {
         &mut self.queue
     }
 }
+
+/// Queue used for uploading data, which may be the graphics queue
+/// when the device does not have a dedicated transfer queue family
+pub struct TransferQueue {
+    pub command_pool: CommandPool,
+    pub queue: Queue,
+    pub family_index: u32,
+}
+
+impl TransferQueue {
+    pub fn new(device: &Device) -> Self {
+        let family_index = device.transfer_queue_index;
+        Self {
+            queue: Queue::new_with_family(device, family_index),
+            command_pool: CommandPool::new_with_family(device, family_index),
+            family_index,
+        }
+    }
+
+    /// Whether uploaded resources need a queue family ownership transfer
+    /// before being used on the graphics queue
+    pub fn is_dedicated(&self, device: &Device) -> bool {
+        self.family_index != device.graphics_queue_index
+    }
+}
+
+impl std::ops::Deref for TransferQueue {
+    type Target = Queue;
+    fn deref(&self) -> &Self::Target {
+        &self.queue
+    }
+}
+
+impl std::ops::DerefMut for TransferQueue {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.queue
+    }
+}
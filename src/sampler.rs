@@ -1,11 +1,105 @@
-// Copyright © 2021-2024
+// Copyright © 2021-2025
 // Author: Antonio Caggiano <info@antoniocaggiano.eu>
 // SPDX-License-Identifier: MIT;
 
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use crate::*;
 
+/// Description of a sampler, which can be used as a key for caching them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SamplerDesc {
+    pub mag_filter: vk::Filter,
+    pub min_filter: vk::Filter,
+    /// `None` samples only the base level of an image
+    pub mipmap_mode: Option<vk::SamplerMipmapMode>,
+    pub address_mode_u: vk::SamplerAddressMode,
+    pub address_mode_v: vk::SamplerAddressMode,
+    /// Enables anisotropic filtering when supported by the device
+    pub anisotropy: bool,
+    /// Turns the sampler into a comparison one, used for sampling depth images
+    pub compare_op: Option<vk::CompareOp>,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self {
+            mag_filter: vk::Filter::NEAREST,
+            min_filter: vk::Filter::NEAREST,
+            mipmap_mode: Some(vk::SamplerMipmapMode::LINEAR),
+            address_mode_u: vk::SamplerAddressMode::REPEAT,
+            address_mode_v: vk::SamplerAddressMode::REPEAT,
+            anisotropy: false,
+            compare_op: None,
+        }
+    }
+}
+
+impl SamplerDesc {
+    /// Comparison sampler for shadow maps, whose linear filtering
+    /// returns the percentage of the 2x2 closest texels passing the comparison
+    pub fn shadow() -> Self {
+        Self {
+            mag_filter: vk::Filter::LINEAR,
+            min_filter: vk::Filter::LINEAR,
+            mipmap_mode: None,
+            address_mode_u: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            address_mode_v: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            anisotropy: false,
+            // Depth is reversed, closer surfaces have greater values
+            compare_op: Some(vk::CompareOp::GREATER_OR_EQUAL),
+        }
+    }
+
+    fn get_address_mode(wrap: Wrap) -> vk::SamplerAddressMode {
+        match wrap {
+            Wrap::Repeat => vk::SamplerAddressMode::REPEAT,
+            Wrap::MirroredRepeat => vk::SamplerAddressMode::MIRRORED_REPEAT,
+            Wrap::ClampToEdge => vk::SamplerAddressMode::CLAMP_TO_EDGE,
+        }
+    }
+}
+
+impl From<&Sampler> for SamplerDesc {
+    fn from(sampler: &Sampler) -> Self {
+        let mag_filter = match sampler.mag_filter {
+            MagFilter::Nearest => vk::Filter::NEAREST,
+            MagFilter::Linear => vk::Filter::LINEAR,
+        };
+
+        let (min_filter, mipmap_mode) = match sampler.min_filter {
+            MinFilter::Nearest => (vk::Filter::NEAREST, None),
+            MinFilter::Linear => (vk::Filter::LINEAR, None),
+            MinFilter::NearestMipmapNearest => {
+                (vk::Filter::NEAREST, Some(vk::SamplerMipmapMode::NEAREST))
+            }
+            MinFilter::LinearMipmapNearest => {
+                (vk::Filter::LINEAR, Some(vk::SamplerMipmapMode::NEAREST))
+            }
+            MinFilter::NearestMipmapLinear => {
+                (vk::Filter::NEAREST, Some(vk::SamplerMipmapMode::LINEAR))
+            }
+            MinFilter::LinearMipmapLinear => {
+                (vk::Filter::LINEAR, Some(vk::SamplerMipmapMode::LINEAR))
+            }
+        };
+
+        Self {
+            mag_filter,
+            min_filter,
+            mipmap_mode,
+            address_mode_u: Self::get_address_mode(sampler.wrap_s),
+            address_mode_v: Self::get_address_mode(sampler.wrap_t),
+            // Only worth it for smoothly filtered textures
+            anisotropy: min_filter == vk::Filter::LINEAR && mipmap_mode.is_some(),
+            compare_op: None,
+        }
+    }
+}
+
 pub struct RenderSampler {
     pub sampler: vk::Sampler,
     device: Arc<ash::Device>,
@@ -30,13 +124,55 @@ impl RenderSampler {
             .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
             .mip_lod_bias(0.0)
             .min_lod(0.0)
-            .max_lod(0.0);
+            // Allow sampling from all the levels of an image
+            .max_lod(vk::LOD_CLAMP_NONE);
 
         let sampler = unsafe { device.create_sampler(&create_info, None) }
             .expect("Failed to create Vulkan sampler");
 
         Self { sampler, device }
     }
+
+    pub fn new_with_desc(device: &Device, desc: &SamplerDesc) -> Self {
+        // Anisotropy needs to be enabled on the device
+        let anisotropy = desc.anisotropy && device.features.sampler_anisotropy == vk::TRUE;
+        let max_anisotropy = if anisotropy {
+            device.properties.limits.max_sampler_anisotropy.min(16.0)
+        } else {
+            1.0
+        };
+
+        // Recommended by the Vulkan spec for emulating non-mipmapped minification filters
+        let (mipmap_mode, max_lod) = match desc.mipmap_mode {
+            Some(mipmap_mode) => (mipmap_mode, vk::LOD_CLAMP_NONE),
+            None => (vk::SamplerMipmapMode::NEAREST, 0.25),
+        };
+
+        let create_info = vk::SamplerCreateInfo::default()
+            .mag_filter(desc.mag_filter)
+            .min_filter(desc.min_filter)
+            .address_mode_u(desc.address_mode_u)
+            .address_mode_v(desc.address_mode_v)
+            .address_mode_w(vk::SamplerAddressMode::REPEAT)
+            .anisotropy_enable(anisotropy)
+            .max_anisotropy(max_anisotropy)
+            .border_color(vk::BorderColor::INT_OPAQUE_BLACK)
+            .unnormalized_coordinates(false)
+            .compare_enable(desc.compare_op.is_some())
+            .compare_op(desc.compare_op.unwrap_or(vk::CompareOp::ALWAYS))
+            .mipmap_mode(mipmap_mode)
+            .mip_lod_bias(0.0)
+            .min_lod(0.0)
+            .max_lod(max_lod);
+
+        let sampler = unsafe { device.create_sampler(&create_info, None) }
+            .expect("Failed to create Vulkan sampler");
+
+        Self {
+            sampler,
+            device: device.device.clone(),
+        }
+    }
 }
 
 impl Drop for RenderSampler {
@@ -46,3 +182,36 @@ impl Drop for RenderSampler {
         }
     }
 }
+
+/// Device-level cache, so that models using the same kind of
+/// sampler end up sharing the same Vulkan sampler object
+pub struct SamplerCache {
+    samplers: Mutex<HashMap<SamplerDesc, Arc<RenderSampler>>>,
+    device: Arc<Device>,
+}
+
+impl SamplerCache {
+    pub fn new(device: &Arc<Device>) -> Self {
+        Self {
+            samplers: Mutex::new(HashMap::new()),
+            device: device.clone(),
+        }
+    }
+
+    /// Returns a sampler matching `desc`, creating it the first time
+    pub fn get(&self, desc: &SamplerDesc) -> Arc<RenderSampler> {
+        let mut samplers = self.samplers.lock().unwrap();
+        samplers
+            .entry(*desc)
+            .or_insert_with(|| Arc::new(RenderSampler::new_with_desc(&self.device, desc)))
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samplers.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -9,15 +9,29 @@ use crate::*;
 /// Represents a scene in the rendering context, containing multiple models and cameras.
 pub struct RenderScene {
     pub glx: Scene,
+    /// Light reaching every surface regardless of the punctual lights
+    pub ambient: Color,
+    /// Multiplies the radiance of the scene before tonemapping
+    pub exposure: f32,
+    /// Color of the pixels without geometry, `None` keeps the clear values of the pass.
+    /// A transparent background is preserved in the present attachment.
+    pub background: Option<Color>,
+    /// Cameras rendered by frames, empty means the default camera on the whole frame
+    pub views: Vec<CameraView>,
     models: Pack<RenderModel>,
     default_model_handle: Handle<RenderModel>,
     dev: Arc<Dev>,
 }
 
 impl RenderScene {
+    /// Creates a new `RenderScene` containing only the default model.
+    pub fn new(dev: &Arc<Dev>) -> Self {
+        Self::new_with_glx(dev, Scene::default(), Pack::new())
+    }
+
     /// Creates a new empty `RenderScene`.
     pub fn load_glx_path<P: AsRef<Path>>(dev: &Arc<Dev>, glx_path: P, assets: &Assets) -> Self {
-        let mut glx = Scene::load_glx_path(glx_path.as_ref(), assets);
+        let glx = Scene::load_glx_path(glx_path.as_ref(), assets);
 
         let dir = glx_path.as_ref().parent().unwrap_or_else(|| Path::new("."));
 
@@ -25,11 +39,14 @@ impl RenderScene {
 
         for model_source in glx.models.iter() {
             let model_path = dir.join(&model_source.uri);
-            let model = Model::load_gltf_path(model_path, assets).expect("Failed to load model");
-            let model = RenderModel::new_with_gltf(dev, assets, model);
+            let model = RenderModel::load_gltf_path(dev, assets, &model_path);
             models.push(model);
         }
 
+        Self::new_with_glx(dev, glx, models)
+    }
+
+    fn new_with_glx(dev: &Arc<Dev>, mut glx: Scene, mut models: Pack<RenderModel>) -> Self {
         // Add a default model to the scene, useful for having a camera at least.
         let default_model = RenderModel::default(dev);
         let default_model_handle = models.push(default_model);
@@ -43,6 +60,10 @@ impl RenderScene {
 
         Self {
             glx,
+            ambient: Color::new(0.03, 0.03, 0.03, 1.0),
+            exposure: 1.0,
+            background: None,
+            views: Vec::new(),
             models,
             default_model_handle,
             dev: dev.clone(),
@@ -138,6 +159,57 @@ impl RenderScene {
         }
     }
 
+    /// Returns the views rendered by frames, which is the default camera when there are none
+    pub fn get_views(&self) -> Vec<CameraView> {
+        if self.views.is_empty() {
+            vec![CameraView::new(self.get_default_camera_draw_info())]
+        } else {
+            self.views.clone()
+        }
+    }
+
+    /// Returns every camera of the models instanced by the nodes of the scene, nested ones
+    /// included. Cameras of a model instanced by many nodes are only returned once.
+    pub fn get_camera_draw_infos(&self) -> Vec<CameraDrawInfo> {
+        let mut ret = vec![];
+        for node in self.glx.root.children.iter().copied() {
+            self.push_scene_node_cameras(node, &mut ret);
+        }
+        ret
+    }
+
+    fn push_scene_node_cameras(&self, hnode: Handle<Node>, ret: &mut Vec<CameraDrawInfo>) {
+        let node = self.get_node(hnode).unwrap();
+        if let Some(hmodel) = node.model {
+            let hmodel: Handle<RenderModel> = hmodel.id.into();
+            let model = self.get_model(hmodel).unwrap();
+            for child in model.get_root().children.iter().copied() {
+                Self::push_model_node_cameras(model, hmodel, child, ret);
+            }
+        }
+        for child in node.children.iter().copied() {
+            self.push_scene_node_cameras(child, ret);
+        }
+    }
+
+    fn push_model_node_cameras(
+        model: &RenderModel,
+        hmodel: Handle<RenderModel>,
+        hnode: Handle<Node>,
+        ret: &mut Vec<CameraDrawInfo>,
+    ) {
+        let node = model.get_node(hnode).unwrap();
+        if let Some(camera) = node.camera {
+            let info = CameraDrawInfo::new(camera, hnode, hmodel);
+            if !ret.contains(&info) {
+                ret.push(info);
+            }
+        }
+        for child in node.children.iter().copied() {
+            Self::push_model_node_cameras(model, hmodel, child, ret);
+        }
+    }
+
     pub fn get_root(&self) -> &Node {
         &self.glx.root
     }
@@ -37,13 +37,25 @@ impl ShaderModule {
         vert_path: &str,
         frag_path: &str,
     ) -> (Self, Self) {
-        let vert_data = SlangProgram::get_entry_point_code(vert_path, "main").unwrap();
-        let frag_data = SlangProgram::get_entry_point_code(frag_path, "main").unwrap();
+        Self::try_create_shaders(device, vert_path, frag_path)
+            .expect("Failed to create Vulkan shader modules")
+    }
 
-        (
-            Self::from_data(device, &vert_data),
-            Self::from_data(device, &frag_data),
-        )
+    /// Compiles the vertex and fragment shaders, returning an error
+    /// instead of panicking when any of them is not valid
+    #[cfg(not(target_os = "android"))]
+    pub fn try_create_shaders(
+        device: &Arc<ash::Device>,
+        vert_path: &str,
+        frag_path: &str,
+    ) -> Result<(Self, Self)> {
+        let vert_data = SlangProgram::get_entry_point_code(vert_path, "main")?;
+        let frag_data = SlangProgram::get_entry_point_code(frag_path, "main")?;
+
+        Ok((
+            Self::try_from_data(device, &vert_data)?,
+            Self::try_from_data(device, &frag_data)?,
+        ))
     }
 
     pub fn new(device: &Arc<ash::Device>, shader_module: vk::ShaderModule) -> Self {
@@ -54,16 +66,36 @@ impl ShaderModule {
     }
 
     pub fn from_path<P: AsRef<Path>>(device: &Arc<ash::Device>, shader_path: P) -> Self {
-        let shader_data = std::fs::read(shader_path).expect("Failed to read shader file");
-        Self::from_data(device, &shader_data)
+        Self::try_from_path(device, shader_path).expect("Failed to create Vulkan shader module")
+    }
+
+    pub fn try_from_path<P: AsRef<Path>>(
+        device: &Arc<ash::Device>,
+        shader_path: P,
+    ) -> Result<Self> {
+        let shader_data = std::fs::read(shader_path)?;
+        Self::try_from_data(device, &shader_data)
     }
 
     pub fn from_data(device: &Arc<ash::Device>, shader_data: &[u8]) -> Self {
-        Self::new(device, Self::build_shader_module(device, shader_data))
+        Self::try_from_data(device, shader_data).expect("Failed to create Vulkan shader module")
     }
 
-    fn build_shader_module(device: &Arc<ash::Device>, shader_data: &[u8]) -> vk::ShaderModule {
-        assert_eq!(shader_data.len() % 4, 0);
+    /// Creates a shader module from SPIR-V bytecode
+    pub fn try_from_data(device: &Arc<ash::Device>, shader_data: &[u8]) -> Result<Self> {
+        Ok(Self::new(
+            device,
+            Self::build_shader_module(device, shader_data)?,
+        ))
+    }
+
+    fn build_shader_module(
+        device: &Arc<ash::Device>,
+        shader_data: &[u8],
+    ) -> Result<vk::ShaderModule> {
+        if !shader_data.len().is_multiple_of(4) {
+            return Err(Error::InvalidSpirv);
+        }
         let mut shader_bytecode = vec![0u32; shader_data.len() / size_of::<u32>()];
         unsafe {
             std::ptr::copy_nonoverlapping(
@@ -74,8 +106,8 @@ impl ShaderModule {
         }
 
         let create_info = vk::ShaderModuleCreateInfo::default().code(&shader_bytecode);
-        unsafe { device.create_shader_module(&create_info, None) }
-            .expect("Failed to create Vulkan shader module")
+        let shader_module = unsafe { device.create_shader_module(&create_info, None) }?;
+        Ok(shader_module)
     }
 
     /// The entrypoint c string should stay alive until the pipeline has been created
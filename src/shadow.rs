@@ -0,0 +1,369 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use crate::*;
+use rayca_pipe::*;
+
+pipewriter!(
+    Shadow,
+    "shaders/shadow.vert.slang",
+    "shaders/shadow.frag.slang"
+);
+
+/// Format of the layers of the shadow atlas
+pub const SHADOW_FORMAT: vk::Format = vk::Format::D32_SFLOAT;
+
+/// How a light casts shadows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowSettings {
+    /// Side of the shadow map in texels, clamped to the size of the atlas
+    pub resolution: u32,
+    /// Depth offset towards the light, which prevents surfaces from shadowing themselves
+    pub bias: f32,
+    /// World space offset along the surface normal, useful at grazing angles
+    pub normal_bias: f32,
+    /// Number of cascades of directional lights, each taking a layer of the atlas
+    pub cascade_count: u32,
+    /// Distance from the camera covered by the cascades of directional lights,
+    /// and range of spot lights without one
+    pub distance: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 1024,
+            bias: 0.0005,
+            normal_bias: 0.02,
+            cascade_count: 3,
+            distance: 50.0,
+        }
+    }
+}
+
+impl ShadowSettings {
+    pub fn resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    pub fn bias(mut self, bias: f32) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    pub fn normal_bias(mut self, normal_bias: f32) -> Self {
+        self.normal_bias = normal_bias;
+        self
+    }
+
+    pub fn cascade_count(mut self, cascade_count: u32) -> Self {
+        self.cascade_count = cascade_count;
+        self
+    }
+
+    pub fn distance(mut self, distance: f32) -> Self {
+        self.distance = distance;
+        self
+    }
+}
+
+/// Same vertices as `Vertex`, but rendered without color attachments by the shadow pass
+pub struct ShadowVertex;
+
+impl VertexInput for ShadowVertex {
+    fn get_bindings() -> Vec<vk::VertexInputBindingDescription> {
+        Vertex::get_bindings()
+    }
+
+    fn get_attributes() -> Vec<vk::VertexInputAttributeDescription> {
+        // Only the position is needed
+        Vertex::get_attributes().into_iter().take(1).collect()
+    }
+
+    fn get_color_blend() -> Vec<vk::PipelineColorBlendAttachmentState> {
+        vec![]
+    }
+}
+
+/// Transforms a point and divides it by its `w` component
+fn transform_point(mat: &Mat4, p: Vec3) -> Vec3 {
+    let row = |r: usize| mat[r][0] * p.x + mat[r][1] * p.y + mat[r][2] * p.z + mat[r][3];
+    let w = row(3);
+    Vec3::new(row(0) / w, row(1) / w, row(2) / w)
+}
+
+/// View matrix of an eye looking along `direction`, rotating the -Z axis onto it
+/// like the nodes of cameras. The roll does not matter for shadow maps.
+fn get_view(eye: Vec3, direction: Vec3) -> Mat4 {
+    let forward = -Vec3::Z_AXIS;
+    let cos_angle = forward.dot(&direction).clamp(-1.0, 1.0);
+    let axis = forward.cross(&direction);
+    let rotation = if axis.len() > 1e-6 {
+        Quat::axis_angle(axis.get_normalized(), cos_angle.acos())
+    } else if cos_angle > 0.0 {
+        Quat::axis_angle(Vec3::Y_AXIS, 0.0)
+    } else {
+        Quat::axis_angle(Vec3::Y_AXIS, std::f32::consts::PI)
+    };
+    Trs::builder()
+        .translation(eye)
+        .rotation(rotation)
+        .build()
+        .get_inversed()
+        .to_mat4()
+}
+
+/// A shadow map as laid out in the storage buffer of the lighting pipeline
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ShadowData {
+    /// From world space to the clip space of the light
+    pub view_proj: Mat4,
+    /// Layer of the atlas with the shadow map
+    pub layer: u32,
+    /// Part of the layer covered by the shadow map, which can be smaller than the atlas
+    pub uv_scale: f32,
+    /// Size of a texel of the atlas in texture coordinates
+    pub texel_size: f32,
+    /// Distance from the camera where this cascade ends, if this is one
+    pub split_distance: f32,
+}
+
+impl ShadowData {
+    /// Position and direction of a light from the world transform of its node
+    fn get_position_and_direction(light: &LightData) -> (Vec3, Vec3) {
+        let model = &light.model;
+        let position = Vec3::new(model[0][3], model[1][3], model[2][3]);
+        // Lights shine along the -Z axis
+        let direction = -Vec3::new(model[0][2], model[1][2], model[2][2]).get_normalized();
+        (position, direction)
+    }
+
+    fn new(view_proj: Mat4, layer: u32, resolution: u32, atlas_size: u32) -> Self {
+        Self {
+            view_proj,
+            layer,
+            uv_scale: resolution as f32 / atlas_size as f32,
+            texel_size: 1.0 / atlas_size as f32,
+            split_distance: f32::MAX,
+        }
+    }
+
+    /// Returns the shadow map of a spot light, rendering the cone with a perspective projection
+    pub fn spot(
+        light: &LightData,
+        outer_cone_angle: f32,
+        settings: &ShadowSettings,
+        layer: u32,
+        atlas_size: u32,
+    ) -> Self {
+        let (position, direction) = Self::get_position_and_direction(light);
+        let fov = (outer_cone_angle * 2.0).clamp(0.01, std::f32::consts::PI - 0.01);
+        let far = if light.range > 0.0 {
+            light.range
+        } else {
+            settings.distance
+        };
+        let view = get_view(position, direction);
+        // Square reverse-Z projection, like cameras
+        let proj = CameraProjection::perspective(fov, 0.05)
+            .far(far)
+            .get_matrix(1.0);
+        let resolution = settings.resolution.min(atlas_size);
+        Self::new(proj * view, layer, resolution, atlas_size)
+    }
+
+    /// Returns the cascades of a directional light, splitting the view frustum of the camera
+    /// up to the shadow distance. Each cascade bounds a slice of the frustum with a sphere,
+    /// so that its projection does not change size while the camera rotates.
+    pub fn cascades(
+        light: &LightData,
+        settings: &ShadowSettings,
+        camera_model: &Mat4,
+        camera_projection: &CameraProjection,
+        aspect: f32,
+        first_layer: u32,
+        atlas_size: u32,
+    ) -> Vec<Self> {
+        let (_, direction) = Self::get_position_and_direction(light);
+
+        // World space corners of the view volume at some distance in front of the camera
+        let get_corners = |distance: f32| {
+            let (width, height) = camera_projection.get_half_extent(aspect, distance);
+            [[-1.0, -1.0], [1.0, -1.0], [-1.0, 1.0], [1.0, 1.0]].map(|[x, y]| {
+                transform_point(camera_model, Vec3::new(x * width, y * height, -distance))
+            })
+        };
+
+        let count = settings.cascade_count.max(1);
+        let resolution = settings.resolution.min(atlas_size);
+        let mut near = 0.0;
+        (0..count)
+            .map(|cascade| {
+                // Quadratic splits give closer cascades more texels
+                let ratio = (cascade + 1) as f32 / count as f32;
+                let far = settings.distance * ratio * ratio;
+
+                let mut corners = get_corners(near).to_vec();
+                corners.extend(get_corners(far));
+                let center = corners
+                    .iter()
+                    .fold(Vec3::default(), |acc, corner| acc + *corner * (1.0 / 8.0));
+                let radius = corners
+                    .iter()
+                    .map(|corner| (*corner - center).len())
+                    .fold(0.0, f32::max);
+                // Rounding reduces shimmering due to tiny changes of the radius
+                let radius = (radius * 16.0).ceil() / 16.0;
+
+                // Casters outside of the slice, but between it and the light, still need to be rendered
+                let caster_distance = settings.distance;
+                let eye = center - direction * (radius + caster_distance);
+                let view = get_view(eye, direction);
+                // Reverse-Z orthographic projection of the bounding sphere, like cameras
+                let proj =
+                    CameraProjection::orthographic(radius, 0.0, 2.0 * radius + caster_distance)
+                        .get_matrix(1.0);
+
+                let mut shadow =
+                    Self::new(proj * view, first_layer + cascade, resolution, atlas_size);
+                shadow.split_distance = far;
+                near = far;
+                shadow
+            })
+            .collect()
+    }
+}
+
+/// Layered depth image where every layer holds the shadow map of a spot light,
+/// or a cascade of a directional light
+pub struct ShadowAtlas {
+    /// Framebuffers are destroyed before the views and the image
+    framebuffers: Vec<vk::Framebuffer>,
+    /// One for each layer, for rendering into it
+    layer_views: Vec<ImageView>,
+    /// Array view of all the layers, for sampling
+    pub view: ImageView,
+    pub image: RenderImage,
+    pub sampler: Arc<RenderSampler>,
+    pub size: u32,
+    device: Arc<ash::Device>,
+}
+
+impl ShadowAtlas {
+    pub fn new(dev: &Dev, pass: &Pass, size: u32, layer_count: u32) -> Self {
+        let mut image = RenderImage::try_new_with_layers(
+            &dev.allocator,
+            size,
+            size,
+            SHADOW_FORMAT,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            1,
+            layer_count,
+        )
+        .expect("Failed to create Vulkan shadow atlas");
+        // Layers without shadows this frame are still sampled
+        let mut uploader = Uploader::new(dev);
+        uploader.transition_image(&mut image, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        uploader.submit().wait();
+
+        let view =
+            ImageView::new_with_layers(&image, vk::ImageViewType::TYPE_2D_ARRAY, 0, layer_count);
+        let layer_views: Vec<ImageView> = (0..layer_count)
+            .map(|layer| ImageView::new_with_layers(&image, vk::ImageViewType::TYPE_2D, layer, 1))
+            .collect();
+
+        let framebuffers = layer_views
+            .iter()
+            .map(|layer_view| {
+                let attachments = [layer_view.view];
+                let create_info = vk::FramebufferCreateInfo::default()
+                    .render_pass(pass.render)
+                    .attachments(&attachments)
+                    .width(size)
+                    .height(size)
+                    .layers(1);
+                unsafe { dev.device.create_framebuffer(&create_info, None) }
+                    .expect("Failed to create Vulkan framebuffer")
+            })
+            .collect();
+
+        let sampler = dev.sampler_cache.get(&SamplerDesc::shadow());
+
+        Self {
+            framebuffers,
+            layer_views,
+            view,
+            image,
+            sampler,
+            size,
+            device: dev.device.device.clone(),
+        }
+    }
+
+    pub fn get_layer_count(&self) -> u32 {
+        self.layer_views.len() as u32
+    }
+
+    pub fn get_framebuffer(&self, layer: u32) -> vk::Framebuffer {
+        self.framebuffers[layer as usize]
+    }
+
+    pub fn get_texture(&self) -> RenderTexture {
+        RenderTexture::new(&self.view, &self.sampler)
+    }
+}
+
+impl Drop for ShadowAtlas {
+    fn drop(&mut self) {
+        unsafe {
+            self.device
+                .device_wait_idle()
+                .expect("Failed to wait for device");
+            for framebuffer in self.framebuffers.drain(..) {
+                self.device.destroy_framebuffer(framebuffer, None);
+            }
+        }
+    }
+}
+
+/// Renders the shadow maps of the lights casting shadows into the atlas of each frame
+pub struct Shadows {
+    pub pipeline: PipelineShadow,
+    pub pass: Pass,
+    /// Side of the layers of the atlas, in texels
+    pub atlas_size: u32,
+    /// Maximum number of shadow maps, where a directional light takes one for each cascade
+    pub layer_count: u32,
+}
+
+impl Shadows {
+    /// Matches the default resolution of `ShadowSettings`, so each layer holds a whole shadow map
+    pub const DEFAULT_ATLAS_SIZE: u32 = 1024;
+    /// Enough for a directional light with its default cascades and a spot light
+    pub const DEFAULT_LAYER_COUNT: u32 = 4;
+
+    pub fn new(
+        #[cfg(target_os = "android")] android_app: &AndroidApp,
+        dev: &Dev,
+        atlas_size: u32,
+        layer_count: u32,
+    ) -> Self {
+        let pass = Pass::shadow(dev);
+        let pipeline = PipelineShadow::new::<ShadowVertex>(
+            #[cfg(target_os = "android")]
+            android_app,
+            &pass,
+        );
+        Self {
+            pipeline,
+            pass,
+            atlas_size,
+            layer_count,
+        }
+    }
+}
@@ -2,16 +2,25 @@
 // Author: Antonio Caggiano <info@antoniocaggiano.eu>
 // SPDX-License-Identifier: MIT
 
-use std::path::Path;
+use std::{fmt::Debug, path::Path};
+
+use crate::{Error, Result};
+
+fn slang_error<E: Debug>(context: &str) -> impl FnOnce(E) -> Error {
+    move |err| Error::Slang(format!("{}: {:?}", context, err))
+}
 
 pub struct SlangProgram {}
 
 impl SlangProgram {
+    /// Compiles the shader at `shader_path` into SPIR-V, returning the code of
+    /// `entry_point_name` or the diagnostics of the failed compilation
     pub fn get_entry_point_code<P: AsRef<Path>>(
         shader_path: P,
         entry_point_name: &str,
-    ) -> Option<Vec<u8>> {
-        let global_session = slang::GlobalSession::new().unwrap();
+    ) -> Result<Vec<u8>> {
+        let global_session = slang::GlobalSession::new()
+            .ok_or_else(|| Error::Slang("Failed to create global session".to_string()))?;
 
         let targets = [slang::TargetDesc::default()
             .format(slang::CompileTarget::Spirv)
@@ -30,13 +39,26 @@ impl SlangProgram {
             .search_paths(&search_paths)
             .options(&session_options);
 
-        let session = global_session.create_session(&session_desc).unwrap();
+        let session = global_session
+            .create_session(&session_desc)
+            .ok_or_else(|| Error::Slang("Failed to create session".to_string()))?;
 
+        let shader_path = shader_path.as_ref();
+        let module_name = shader_path
+            .to_str()
+            .ok_or_else(|| Error::Slang(format!("Invalid shader path {:?}", shader_path)))?;
         let module = session
-            .load_module(&shader_path.as_ref().to_str().unwrap())
-            .unwrap();
+            .load_module(module_name)
+            .map_err(slang_error(&format!("Failed to load {}", module_name)))?;
 
-        let entry_point = module.find_entry_point_by_name(entry_point_name).unwrap();
+        let entry_point = module
+            .find_entry_point_by_name(entry_point_name)
+            .ok_or_else(|| {
+                Error::Slang(format!(
+                    "Failed to find entry point {} in {}",
+                    entry_point_name, module_name
+                ))
+            })?;
 
         use slang::Downcast;
         let program = session
@@ -44,14 +66,16 @@ impl SlangProgram {
                 module.downcast().clone(),
                 entry_point.downcast().clone(),
             ])
-            .expect("Failed to create program");
+            .map_err(slang_error("Failed to create program"))?;
 
-        let linked_program = program.link().expect("Failed to link program");
+        let linked_program = program
+            .link()
+            .map_err(slang_error("Failed to link program"))?;
 
         let shader_blob = linked_program
             .entry_point_code(0, 0)
-            .expect("Failed to get entry point code");
+            .map_err(slang_error("Failed to get entry point code"))?;
 
-        Some(shader_blob.as_slice().to_vec())
+        Ok(shader_blob.as_slice().to_vec())
     }
 }
@@ -42,6 +42,10 @@ impl Swapchain {
             size = Size2::new(size.height, size.width);
         }
 
+        // Transfer source usage allows reading back the content of swapchain images
+        let image_usage = vk::ImageUsageFlags::COLOR_ATTACHMENT
+            | (surface_capabilities.supported_usage_flags & vk::ImageUsageFlags::TRANSFER_SRC);
+
         let mut extent = surface_capabilities.min_image_extent;
         extent.width = extent.width.max(size.width);
         extent.height = extent.height.max(size.height);
@@ -54,7 +58,7 @@ impl Swapchain {
                 .image_color_space(dev.surface_format.color_space)
                 .image_extent(extent)
                 .image_array_layers(1)
-                .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
+                .image_usage(image_usage)
                 .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
                 .pre_transform(current_transform)
                 .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
@@ -68,6 +68,12 @@ impl Fence {
         }
     }
 
+    /// Returns whether the fence has been signaled, without blocking
+    pub fn is_signaled(&self) -> bool {
+        unsafe { self.device.get_fence_status(self.fence) }
+            .expect("Failed to get Vulkan fence status")
+    }
+
     pub fn reset(&mut self) {
         self.can_wait = false;
         unsafe { self.device.reset_fences(&[self.fence]) }.expect("Failed to reset Vulkan fence");
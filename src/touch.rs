@@ -0,0 +1,216 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+pub use winit::event::TouchPhase;
+
+use crate::*;
+
+pub struct Finger {
+    pub phase: TouchPhase,
+    pub start: Vec2,
+    pub start_time: Instant,
+    pub position: Vec2,
+    /// Accumulated since the last update
+    pub movement: Vec2,
+    /// Whether it went farther than the tap slop from where it started
+    moved: bool,
+    /// Whether other fingers touched the screen at the same time
+    multi: bool,
+}
+
+impl Finger {
+    pub fn is_active(&self) -> bool {
+        matches!(self.phase, TouchPhase::Started | TouchPhase::Moved)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// A single finger lifted quickly without moving
+    Tap { position: Vec2 },
+    /// A second tap close in time and space to the previous one, which is reported as well
+    DoubleTap { position: Vec2 },
+    /// A single finger moving after going past the tap slop
+    Drag { position: Vec2, delta: Vec2 },
+    /// Two fingers moving apart, when `scale` is greater than 1, or closer
+    Pinch { center: Vec2, scale: f32 },
+    /// Two fingers turning clockwise on screen, when `angle` is positive
+    Rotate { center: Vec2, angle: f32 },
+}
+
+/// Thresholds used to tell gestures apart
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureConfig {
+    /// Pixels a finger can move while still tapping
+    pub tap_slop: f32,
+    pub tap_duration: Duration,
+    pub double_tap_interval: Duration,
+    /// Pixels between two taps of a double tap
+    pub double_tap_slop: f32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            tap_slop: 16.0,
+            tap_duration: Duration::from_millis(300),
+            double_tap_interval: Duration::from_millis(300),
+            double_tap_slop: 48.0,
+        }
+    }
+}
+
+/// Fingers on the screen by id, and the gestures they made since the last update
+#[derive(Default)]
+pub struct Touches {
+    /// Fingers touching the screen, or lifted since the last update
+    pub fingers: BTreeMap<u64, Finger>,
+    pub gestures: Vec<Gesture>,
+    pub config: GestureConfig,
+    last_tap: Option<(Instant, Vec2)>,
+}
+
+fn get_distance(a: Vec2, b: Vec2) -> f32 {
+    ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt()
+}
+
+impl Touches {
+    pub fn update(&mut self) {
+        self.fingers.retain(|_, finger| finger.is_active());
+        for finger in self.fingers.values_mut() {
+            finger.movement = Vec2::ZERO;
+        }
+        self.gestures.clear();
+    }
+
+    pub fn get_active_count(&self) -> usize {
+        self.fingers.values().filter(|f| f.is_active()).count()
+    }
+
+    /// Applies an event of the window system, recognizing gestures as fingers move and lift
+    pub fn handle(&mut self, id: u64, phase: TouchPhase, position: Vec2, time: Instant) {
+        match phase {
+            TouchPhase::Started => {
+                let multi = self.get_active_count() > 0;
+                for finger in self.fingers.values_mut() {
+                    finger.multi |= multi;
+                }
+                let finger = Finger {
+                    phase,
+                    start: position,
+                    start_time: time,
+                    position,
+                    movement: Vec2::ZERO,
+                    moved: false,
+                    multi,
+                };
+                self.fingers.insert(id, finger);
+            }
+            TouchPhase::Moved => self.handle_move(id, position),
+            TouchPhase::Ended => {
+                self.handle_move(id, position);
+                let Some(finger) = self.fingers.get_mut(&id) else {
+                    return;
+                };
+                finger.phase = phase;
+                let is_tap = !finger.moved
+                    && !finger.multi
+                    && time.duration_since(finger.start_time) <= self.config.tap_duration;
+                if is_tap {
+                    self.handle_tap(position, time);
+                }
+            }
+            TouchPhase::Cancelled => {
+                if let Some(finger) = self.fingers.get_mut(&id) {
+                    finger.phase = phase;
+                }
+            }
+        }
+    }
+
+    fn handle_move(&mut self, id: u64, position: Vec2) {
+        let Some(finger) = self.fingers.get_mut(&id) else {
+            return;
+        };
+        let previous = finger.position;
+        let delta = Vec2::new(position.x - previous.x, position.y - previous.y);
+        if delta.x == 0.0 && delta.y == 0.0 {
+            return;
+        }
+        finger.position = position;
+        finger.movement.x += delta.x;
+        finger.movement.y += delta.y;
+        finger.moved |= get_distance(finger.start, position) > self.config.tap_slop;
+        let (moved, multi) = (finger.moved, finger.multi);
+
+        let mut others = self
+            .fingers
+            .iter()
+            .filter(|(other, finger)| **other != id && finger.is_active());
+        match (others.next(), others.next()) {
+            (None, _) if moved && !multi => {
+                self.gestures.push(Gesture::Drag { position, delta });
+            }
+            // Two fingers, one of which moved while the other stood still
+            (Some((_, other)), None) => {
+                let pivot = other.position;
+                let before = get_distance(pivot, previous);
+                let after = get_distance(pivot, position);
+                let center = Vec2::new((pivot.x + position.x) * 0.5, (pivot.y + position.y) * 0.5);
+                if before > 0.0 && after > 0.0 {
+                    self.gestures.push(Gesture::Pinch {
+                        center,
+                        scale: after / before,
+                    });
+                    let angle_before = (previous.y - pivot.y).atan2(previous.x - pivot.x);
+                    let angle_after = (position.y - pivot.y).atan2(position.x - pivot.x);
+                    let angle = (angle_after - angle_before + std::f32::consts::PI)
+                        .rem_euclid(std::f32::consts::TAU)
+                        - std::f32::consts::PI;
+                    self.gestures.push(Gesture::Rotate { center, angle });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_tap(&mut self, position: Vec2, time: Instant) {
+        self.gestures.push(Gesture::Tap { position });
+        let double = self.last_tap.is_some_and(|(last_time, last_position)| {
+            time.duration_since(last_time) <= self.config.double_tap_interval
+                && get_distance(last_position, position) <= self.config.double_tap_slop
+        });
+        if double {
+            self.gestures.push(Gesture::DoubleTap { position });
+            // A third tap starts over
+            self.last_tap = None;
+        } else {
+            self.last_tap = Some((time, position));
+        }
+    }
+
+    /// Returns the product of the pinches since the last update, 1 when there were none
+    pub fn get_pinch(&self) -> f32 {
+        self.gestures
+            .iter()
+            .map(|gesture| match gesture {
+                Gesture::Pinch { scale, .. } => *scale,
+                _ => 1.0,
+            })
+            .product()
+    }
+
+    /// Returns the sum of the drags since the last update
+    pub fn get_drag(&self) -> Vec2 {
+        self.gestures
+            .iter()
+            .fold(Vec2::ZERO, |sum, gesture| match gesture {
+                Gesture::Drag { delta, .. } => Vec2::new(sum.x + delta.x, sum.y + delta.y),
+                _ => sum,
+            })
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use crate::*;
+
+/// Records staging copies into a single command buffer of the transfer queue,
+/// so that many images can be uploaded with one submission.
+///
+/// When the transfer queue belongs to a dedicated family, ownership of the
+/// uploaded images is released by the transfer queue and acquired by the
+/// graphics queue, where their mip chains are generated.
+pub struct Uploader {
+    staging: Vec<RenderBuffer>,
+    command_buffer: CommandBuffer,
+    /// Acquires ownership of the images on the graphics queue,
+    /// only needed when the transfer queue family is a dedicated one
+    graphics_command_buffer: Option<CommandBuffer>,
+    transfer_queue: Queue,
+    graphics_queue: Queue,
+    allocator: Arc<Allocator>,
+}
+
+impl Uploader {
+    pub fn new(dev: &Dev) -> Self {
+        Self::new_with_queues(&dev.allocator, &dev.graphics_queue, &dev.transfer_queue)
+    }
+
+    /// Useful for uploading resources while the `Dev` is still being created
+    pub fn new_with_queues(
+        allocator: &Arc<Allocator>,
+        graphics_queue: &GraphicsQueue,
+        transfer_queue: &TransferQueue,
+    ) -> Self {
+        let command_buffer = CommandBuffer::new(&transfer_queue.command_pool);
+        command_buffer.begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+        let graphics_command_buffer = if transfer_queue.is_dedicated(&allocator.device) {
+            let command_buffer = CommandBuffer::new(&graphics_queue.command_pool);
+            command_buffer.begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+            Some(command_buffer)
+        } else {
+            None
+        };
+
+        Self {
+            staging: vec![],
+            command_buffer,
+            graphics_command_buffer,
+            transfer_queue: transfer_queue.queue.clone(),
+            graphics_queue: graphics_queue.queue.clone(),
+            allocator: allocator.clone(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.staging.is_empty()
+    }
+
+    /// Returns the allocator of the images created for this upload
+    pub fn get_allocator(&self) -> &Arc<Allocator> {
+        &self.allocator
+    }
+
+    /// Records a transition of all the levels and layers of `image` on the graphics queue,
+    /// useful for images which are not uploaded, such as attachments
+    pub fn transition_image(&mut self, image: &mut RenderImage, new_layout: vk::ImageLayout) {
+        let command_buffer = self
+            .graphics_command_buffer
+            .as_ref()
+            .unwrap_or(&self.command_buffer);
+        image.transition(command_buffer, new_layout);
+    }
+
+    /// Records a copy of `data` into `image`, which needs to be created with
+    /// `TRANSFER_DST` and `SAMPLED` usage, generating its mip chain if it has one.
+    /// All levels are going to be shader read only once the token returned by `submit` is ready.
+    pub fn upload_image(&mut self, image: &mut RenderImage, data: &[u8]) -> Result<()> {
+        self.upload_levels(image, data, &[0])
+    }
+
+    /// Like `upload_image`, but copies all the levels of `data`. Remaining levels of `image`,
+    /// if any, are generated from the last one. See `RenderImage::try_sampled_for_data`.
+    pub fn upload_image_data(&mut self, image: &mut RenderImage, data: &ImageData) -> Result<()> {
+        self.upload_levels(image, &data.levels.concat(), &data.get_level_offsets())
+    }
+
+    fn upload_levels(
+        &mut self,
+        image: &mut RenderImage,
+        data: &[u8],
+        level_offsets: &[vk::DeviceSize],
+    ) -> Result<()> {
+        let staging =
+            RenderBuffer::try_from_data(&self.allocator, data, vk::BufferUsageFlags::TRANSFER_SRC)?;
+
+        image.copy_levels_from(&staging, level_offsets, &self.command_buffer);
+        let first_generated_level = level_offsets.len() as u32;
+
+        let Some(graphics_command_buffer) = self.graphics_command_buffer.as_ref() else {
+            image.generate_mips_from(first_generated_level, &self.command_buffer);
+            self.staging.push(staging);
+            return Ok(());
+        };
+
+        // Release from the transfer queue family to the graphics one, keeping the layout
+        // as blits for generating mips are only available on the graphics queue.
+        // The acquire barrier on the graphics queue needs to match this one.
+        let ownership_barrier = vk::ImageMemoryBarrier::default()
+            .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+            .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+            .src_queue_family_index(self.allocator.device.transfer_queue_index)
+            .dst_queue_family_index(self.allocator.device.graphics_queue_index)
+            .image(image.image)
+            .subresource_range(
+                vk::ImageSubresourceRange::default()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .base_mip_level(0)
+                    .level_count(image.mip_levels)
+                    .base_array_layer(0)
+                    .layer_count(1),
+            );
+
+        let release_barriers =
+            vec![ownership_barrier.src_access_mask(vk::AccessFlags::TRANSFER_WRITE)];
+        self.command_buffer.pipeline_barriers(
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+            vk::DependencyFlags::default(),
+            &release_barriers,
+        );
+
+        let acquire_barriers = vec![
+            ownership_barrier
+                .dst_access_mask(vk::AccessFlags::TRANSFER_READ | vk::AccessFlags::TRANSFER_WRITE),
+        ];
+        graphics_command_buffer.pipeline_barriers(
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::default(),
+            &acquire_barriers,
+        );
+        image.generate_mips_from(first_generated_level, graphics_command_buffer);
+
+        self.staging.push(staging);
+        Ok(())
+    }
+
+    /// Submits all the recorded copies at once, returning a token which
+    /// keeps the staging buffers alive until the upload has finished
+    pub fn submit(self) -> UploadToken {
+        self.command_buffer.end();
+
+        let mut fence = Fence::unsignaled(&self.allocator.device.device);
+        let mut command_buffers = vec![self.command_buffer];
+
+        let Some(graphics_command_buffer) = self.graphics_command_buffer else {
+            let commands = [command_buffers[0].command_buffer];
+            let submits = [vk::SubmitInfo::default().command_buffers(&commands)];
+            self.transfer_queue.submit(&submits, Some(&mut fence));
+
+            return UploadToken {
+                fence,
+                _semaphore: None,
+                _command_buffers: command_buffers,
+                _staging: self.staging,
+            };
+        };
+
+        // Transfer queue signals the graphics queue when copies are done
+        let semaphore = Semaphore::new(&self.allocator.device.device);
+        let signals = [semaphore.semaphore];
+        let commands = [command_buffers[0].command_buffer];
+        let submits = [vk::SubmitInfo::default()
+            .command_buffers(&commands)
+            .signal_semaphores(&signals)];
+        self.transfer_queue.submit(&submits, None);
+
+        // Graphics queue acquires ownership of the uploaded images
+        graphics_command_buffer.end();
+        let waits = [semaphore.semaphore];
+        let wait_dst_stage_mask = [vk::PipelineStageFlags::TRANSFER];
+        let commands = [graphics_command_buffer.command_buffer];
+        let submits = [vk::SubmitInfo::default()
+            .wait_semaphores(&waits)
+            .wait_dst_stage_mask(&wait_dst_stage_mask)
+            .command_buffers(&commands)];
+        self.graphics_queue.submit(&submits, Some(&mut fence));
+        command_buffers.push(graphics_command_buffer);
+
+        UploadToken {
+            fence,
+            _semaphore: Some(semaphore),
+            _command_buffers: command_buffers,
+            _staging: self.staging,
+        }
+    }
+}
+
+/// Returned by `Uploader::submit`, it can be polled or waited on
+/// before using the uploaded resources. Dropping it waits for the upload.
+pub struct UploadToken {
+    /// Declared first so that it waits for the upload before anything else is dropped
+    fence: Fence,
+    _semaphore: Option<Semaphore>,
+    _command_buffers: Vec<CommandBuffer>,
+    _staging: Vec<RenderBuffer>,
+}
+
+impl UploadToken {
+    /// Returns whether the upload has finished, without blocking
+    pub fn is_ready(&self) -> bool {
+        !self.fence.can_wait || self.fence.is_signaled()
+    }
+
+    /// Blocks until the upload has finished
+    pub fn wait(&mut self) {
+        self.fence.wait();
+    }
+}
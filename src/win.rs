@@ -2,6 +2,7 @@
 // Author: Antonio Caggiano <info@antoniocaggiano.eu>
 // SPDX-License-Identifier: MIT
 
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 use crate::*;
@@ -11,7 +12,7 @@ use winit::{
     dpi::PhysicalSize,
     event::*,
     event_loop::ActiveEventLoop,
-    keyboard::{KeyCode, NativeKeyCode, PhysicalKey},
+    keyboard::{NativeKeyCode, PhysicalKey},
     window::{Icon, Window, WindowId, WindowLevel},
 };
 
@@ -94,6 +95,9 @@ pub struct Win {
     resized: bool,
     pub exit: bool,
 
+    /// Devices which started touches, as opposed to joysticks
+    touch_devices: HashSet<DeviceId>,
+
     pub input: Input,
 }
 
@@ -118,6 +122,7 @@ impl Win {
             window: None,
             resized: false,
             exit: false,
+            touch_devices: HashSet::new(),
             input: Input::default(),
         }
     }
@@ -132,6 +137,7 @@ impl Win {
             window: None,
             resized: false,
             exit: false,
+            touch_devices: HashSet::new(),
             input: Input::default(),
         }
     }
@@ -172,83 +178,85 @@ impl ApplicationHandler for Win {
                     KeyEvent {
                         physical_key,
                         state,
+                        text,
                         ..
                     },
                 ..
             } => match physical_key {
-                PhysicalKey::Code(KeyCode::KeyQ) => match state {
-                    ElementState::Pressed => self.input.q = ButtonState::JustPressed,
-                    ElementState::Released => self.input.q = ButtonState::JustReleased,
-                },
-                PhysicalKey::Code(KeyCode::KeyW) => match state {
-                    ElementState::Pressed => self.input.w = ButtonState::JustPressed,
-                    ElementState::Released => self.input.w = ButtonState::JustReleased,
-                },
-                PhysicalKey::Code(KeyCode::KeyE) => match state {
-                    ElementState::Pressed => self.input.e = ButtonState::JustPressed,
-                    ElementState::Released => self.input.e = ButtonState::JustReleased,
-                },
-                PhysicalKey::Code(KeyCode::KeyA) => match state {
-                    ElementState::Pressed => self.input.a = ButtonState::JustPressed,
-                    ElementState::Released => self.input.a = ButtonState::JustReleased,
-                },
-                PhysicalKey::Code(KeyCode::KeyS) => match state {
-                    ElementState::Pressed => self.input.s = ButtonState::JustPressed,
-                    ElementState::Released => self.input.s = ButtonState::JustReleased,
-                },
-                PhysicalKey::Code(KeyCode::KeyD) => match state {
-                    ElementState::Pressed => self.input.d = ButtonState::JustPressed,
-                    ElementState::Released => self.input.d = ButtonState::JustReleased,
-                },
+                PhysicalKey::Code(code) => {
+                    self.input.set_key(code, state.is_pressed());
+                    if let Some(text) = text.filter(|_| state.is_pressed()) {
+                        self.input.text.push_str(&text);
+                    }
+                }
                 PhysicalKey::Unidentified(NativeKeyCode::Android(code)) => {
-                    let button_state = match state {
-                        ElementState::Pressed => ButtonState::JustPressed,
-                        ElementState::Released => ButtonState::JustReleased,
-                    };
-                    match AndroidKeyCode::from(code) {
-                        AndroidKeyCode::Back => self.input.android.back = button_state,
-                        AndroidKeyCode::A => self.input.android.a = button_state,
-                        AndroidKeyCode::B => self.input.android.b = button_state,
-                        AndroidKeyCode::X => self.input.android.x = button_state,
-                        AndroidKeyCode::Y => self.input.android.y = button_state,
-                        AndroidKeyCode::L1 => self.input.android.l1 = button_state,
-                        AndroidKeyCode::R1 => self.input.android.r1 = button_state,
-                        AndroidKeyCode::L2 => self.input.android.l2 = button_state,
-                        AndroidKeyCode::R2 => self.input.android.r2 = button_state,
-                        AndroidKeyCode::L3 => self.input.android.l3 = button_state,
-                        AndroidKeyCode::R3 => self.input.android.r3 = button_state,
-                        AndroidKeyCode::Play => self.input.android.play = button_state,
-                        AndroidKeyCode::Stop => self.input.android.stop = button_state,
-                        _ => (),
+                    let code = AndroidKeyCode::from(code);
+                    if let Some(button) = self.input.android.get_button_mut(code) {
+                        button.set_down(state.is_pressed());
+                    }
+                    // Android delivers gamepad buttons as keys, regardless of the device
+                    if let Some(button) = code.get_gamepad_button() {
+                        let value = state.is_pressed() as i32 as f32;
+                        let Some(gamepad) = self.input.get_first_gamepad_mut() else {
+                            return;
+                        };
+                        match button {
+                            GamepadButton::LeftTrigger => {
+                                gamepad.set_axis(GamepadAxis::LeftTrigger, value)
+                            }
+                            GamepadButton::RightTrigger => {
+                                gamepad.set_axis(GamepadAxis::RightTrigger, value)
+                            }
+                            button => gamepad.set_button(button, state.is_pressed()),
+                        }
                     }
                 }
                 _ => println!("Unhandled key event: {:?}", physical_key),
             },
-            WindowEvent::MouseInput {
-                state,
-                button: MouseButton::Left,
-                ..
-            } => match state {
-                ElementState::Pressed => self.input.mouse.left = ButtonState::JustPressed,
-                ElementState::Released => self.input.mouse.left = ButtonState::JustReleased,
-            },
-            WindowEvent::MouseInput {
-                state,
-                button: MouseButton::Right,
-                ..
-            } => match state {
-                ElementState::Pressed => self.input.mouse.right = ButtonState::JustPressed,
-                ElementState::Released => self.input.mouse.right = ButtonState::JustReleased,
-            },
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.input.mouse.set_button(button, state.is_pressed())
+            }
+            WindowEvent::ModifiersChanged(modifiers) => self.input.modifiers = modifiers.state(),
+            WindowEvent::Focused(false) => self.input.release_all(),
+            WindowEvent::Ime(Ime::Commit(text)) => self.input.text.push_str(&text),
             WindowEvent::CursorMoved { position, .. } => {
-                self.input.mouse.movement.x = position.x as f32 - self.input.mouse.position.x;
-                self.input.mouse.movement.y = position.y as f32 - self.input.mouse.position.y;
+                // Several events may arrive within the same frame
+                self.input.mouse.movement.x += position.x as f32 - self.input.mouse.position.x;
+                self.input.mouse.movement.y += position.y as f32 - self.input.mouse.position.y;
                 self.input.mouse.position.x = position.x as f32;
                 self.input.mouse.position.y = position.y as f32;
             }
-            WindowEvent::Touch(Touch { location, .. }) => {
-                self.input.android.left_axis.x = location.x as f32;
-                self.input.android.left_axis.y = location.y as f32;
+            WindowEvent::MouseWheel { delta, .. } => {
+                let (x, y) = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => (x, y),
+                    // Touchpads report pixels, roughly 20 per wheel step
+                    MouseScrollDelta::PixelDelta(position) => {
+                        (position.x as f32 / 20.0, position.y as f32 / 20.0)
+                    }
+                };
+                self.input.mouse.scroll.x += x;
+                self.input.mouse.scroll.y += y;
+            }
+            WindowEvent::Touch(Touch {
+                device_id,
+                id,
+                location,
+                phase,
+                ..
+            }) => {
+                if phase == TouchPhase::Started {
+                    self.touch_devices.insert(device_id);
+                } else if !self.touch_devices.contains(&device_id) {
+                    // Android reports the X and Y axes of joysticks as touches which never start
+                    let axes = Vec2::new(location.x as f32, location.y as f32);
+                    self.input.set_android_joystick(axes);
+                    return;
+                }
+                let position = Vec2::new(location.x as f32, location.y as f32);
+                let touches = &mut self.input.touches;
+                touches.handle(id, phase, position, std::time::Instant::now());
+                let down = touches.get_active_count() > 0;
+                self.input.touch.set_down(down);
             }
             WindowEvent::CloseRequested => {
                 self.window = None;
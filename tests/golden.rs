@@ -0,0 +1,422 @@
+// Copyright © 2025
+// Author: Antonio Caggiano <info@antoniocaggiano.eu>
+// SPDX-License-Identifier: MIT
+
+//! Golden-image tests render scenes offscreen and compare the result against
+//! reference images stored in `tests/golden`. A missing reference fails the test,
+//! and references are only written or regenerated by setting `RAYCA_BLESS=1`.
+
+use std::{path::PathBuf, sync::Arc};
+
+use image::{Rgba, RgbaImage};
+use rayca_core::*;
+use rayca_pipe::*;
+
+pipewriter!(
+    Unlit,
+    "shaders/unlit.vert.slang",
+    "shaders/unlit.frag.slang"
+);
+
+impl RenderPipeline for PipelineUnlit {
+    fn render(
+        &self,
+        frame: &mut Frame,
+        scene: &RenderScene,
+        camera_infos: &[CameraDrawInfo],
+        infos: Vec<DrawInfo>,
+    ) {
+        self.bind(&frame.cache);
+
+        for camera_info in camera_infos {
+            let view_key = ViewMatrixKey {
+                model: camera_info.model,
+                node: camera_info.node,
+            };
+            let proj_key = ProjMatrixKey {
+                model: camera_info.model,
+                camera: camera_info.camera,
+                view: frame.current_view,
+            };
+            let proj_buffer = frame.cache.proj_buffers.get(&proj_key).unwrap();
+            let key = DescriptorKey::builder()
+                .layout(self.get_layout())
+                .model(camera_info.model)
+                .node(camera_info.node)
+                .camera(camera_info.camera)
+                .buffer(proj_buffer.buffer)
+                .build();
+            self.bind_view_and_proj(
+                &frame.cache.command_buffer,
+                &mut frame.cache.descriptors,
+                key,
+                frame.cache.view_buffers.get(&view_key).unwrap(),
+                proj_buffer,
+            );
+
+            for info in infos.iter() {
+                let model = scene.get_model(info.model).unwrap();
+
+                let model_key = ModelMatrixKey {
+                    model: info.model,
+                    node: info.node,
+                };
+                let normal_key = NormalMatrixKey {
+                    model: info.model,
+                    node: info.node,
+                    view: Handle::NONE,
+                };
+                let key = DescriptorKey::builder()
+                    .layout(self.get_layout())
+                    .model(info.model)
+                    .node(info.node)
+                    .build();
+                self.bind_model_and_normal_matrix(
+                    &frame.cache.command_buffer,
+                    &mut frame.cache.descriptors,
+                    key,
+                    frame.cache.model_buffers.get(&model_key).unwrap(),
+                    frame.cache.normal_buffers.get(&normal_key).unwrap(),
+                );
+
+                let primitive = model.get_primitive(info.primitive).unwrap();
+                let material_key = MaterialKey {
+                    model: info.model,
+                    material: primitive.material,
+                };
+                let color = frame
+                    .cache
+                    .material_buffers
+                    .get(&material_key)
+                    .unwrap_or(&frame.dev.fallback.white_buffer);
+                let key = DescriptorKey::builder()
+                    .layout(self.get_layout())
+                    .model(info.model)
+                    .material(primitive.material)
+                    .build();
+                self.bind_color(
+                    &frame.cache.command_buffer,
+                    &mut frame.cache.descriptors,
+                    key,
+                    color,
+                );
+
+                let render_primitive = model.primitives.get(info.primitive.id.into()).unwrap();
+                self.draw(&frame.cache, render_primitive);
+            }
+        }
+    }
+}
+
+/// Maximum difference allowed for each channel of each pixel, as software
+/// and hardware implementations do not rasterize exactly the same way
+const TOLERANCE: u8 = 2;
+
+const SIZE: u32 = 128;
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn output_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+/// Returns a scene with a triangle in front of the default camera
+fn triangle_scene(dev: &Arc<Dev>) -> RenderScene {
+    let assets = Assets::new();
+    let mut scene = RenderScene::new(dev);
+
+    let model = RenderModel::load_gltf_path(dev, &assets, golden_dir().join("triangle.gltf"));
+    let hmodel = scene.push_model(model);
+
+    let node = Node::builder()
+        .name("Triangle")
+        .model(hmodel.id.into())
+        .trs(
+            Trs::builder()
+                .translation(Vec3::new(0.0, 2.0, -2.0))
+                .build(),
+        )
+        .build();
+    let hnode = scene.glx.nodes.push(node);
+    scene.glx.root.children.push(hnode);
+
+    scene
+}
+
+/// Returns the triangle scene as described by `triangle.glx`
+fn glx_triangle_scene(dev: &Arc<Dev>) -> RenderScene {
+    RenderScene::load_glx_path(dev, golden_dir().join("triangle.glx"), &Assets::new())
+}
+
+/// Returns the triangle scene lit by a directional light shining towards the triangle
+fn lit_triangle_scene(dev: &Arc<Dev>) -> RenderScene {
+    lit_triangle_scene_with(dev, lit_triangle_light())
+}
+
+fn lit_triangle_light() -> PunctualLight {
+    PunctualLight::directional()
+        .color(Color::new(1.0, 0.8, 0.6, 1.0))
+        .intensity(0.75)
+}
+
+/// Like `lit_triangle_scene`, but the light casts shadows onto the triangle itself,
+/// which should not end up shadowed thanks to the bias
+fn shadowed_triangle_scene(dev: &Arc<Dev>) -> RenderScene {
+    let light = lit_triangle_light().shadow(ShadowSettings::default().resolution(256));
+    lit_triangle_scene_with(dev, light)
+}
+
+/// Returns the triangle scene seen by the default camera, with a view in the middle
+/// of the frame from a camera looking away from the triangle on top of it
+fn overlapping_views_scene(dev: &Arc<Dev>) -> RenderScene {
+    let mut scene = triangle_scene(dev);
+    let front = scene.get_default_camera_draw_info();
+
+    let model = scene.get_default_model_mut();
+    let hcamera = model.push_camera(Camera::default());
+    let hnode = model.push_node(
+        Node::builder()
+            .camera(hcamera)
+            .trs(
+                Trs::builder()
+                    .translation(Vec3::new(0.0, 2.0, 0.0))
+                    .rotation(Quat::axis_angle(Vec3::Y_AXIS, std::f32::consts::PI))
+                    .build(),
+            )
+            .build(),
+    );
+    model.push_to_scene(hnode);
+    let back = CameraDrawInfo::new(hcamera, hnode, front.model);
+
+    scene.views = vec![
+        CameraView::new(front),
+        CameraView::new(back).offset(0.25, 0.25).extent(0.5, 0.5),
+    ];
+    scene
+}
+
+fn lit_triangle_scene_with(dev: &Arc<Dev>, light: PunctualLight) -> RenderScene {
+    let mut scene = triangle_scene(dev);
+
+    let model = scene.get_default_model_mut();
+    let hlight = model.push_light(light);
+    let hnode = model.push_node(Node::builder().name("Light").build());
+    model.attach_light(hnode, hlight);
+    model.push_to_scene(hnode);
+
+    scene
+}
+
+/// Renders the triangle scene offscreen, resolving the geometry
+/// subpass with the pipeline returned by `create_resolve`
+fn render<P: RenderPipeline>(create_resolve: impl FnOnce(&Pass) -> P) -> RgbaImage {
+    render_with(PipelineUnlit::new::<Vertex>, create_resolve)
+}
+
+/// Like `render`, but draws the triangle with the pipeline returned by `create_geometry`
+fn render_with<G: RenderPipeline, P: RenderPipeline>(
+    create_geometry: impl FnOnce(&Pass) -> G,
+    create_resolve: impl FnOnce(&Pass) -> P,
+) -> RgbaImage {
+    render_scene(triangle_scene, create_geometry, create_resolve)
+}
+
+/// Like `render_with`, but renders the scene returned by `create_scene`
+fn render_scene<G: RenderPipeline, P: RenderPipeline>(
+    create_scene: impl FnOnce(&Arc<Dev>) -> RenderScene,
+    create_geometry: impl FnOnce(&Pass) -> G,
+    create_resolve: impl FnOnce(&Pass) -> P,
+) -> RgbaImage {
+    let ctx = Ctx::builder().build();
+    let dev = Arc::new(Dev::new(&ctx, None));
+    let pass = Pass::offscreen(&dev);
+    let mut frames = OffscreenFrames::new(&dev, Size2::new(SIZE, SIZE), &pass, 1);
+
+    let pipelines: Vec<Box<dyn RenderPipeline>> = vec![Box::new(create_geometry(&pass))];
+    let resolve = create_resolve(&pass);
+    let shadows = Shadows::new(&dev, 256, 4);
+    let scene = create_scene(&dev);
+
+    let mut frame = frames.next_frame().unwrap();
+    frame.begin(&scene);
+    frame.render_shadows(&scene, &shadows);
+    frame.begin_render(&pass);
+    frame.set_viewport_and_scissor(1.0, false);
+    frame.draw(&scene, &pipelines);
+    frame.end(&scene, &resolve);
+
+    let id = frame.id;
+    frames.present(&dev, frame).unwrap();
+    frames.get_frame_mut(id).unwrap().read_pixels()
+}
+
+/// Returns an image highlighting in red the pixels which differ more than `tolerance`,
+/// or `None` when the images match
+fn compare_images(actual: &RgbaImage, expected: &RgbaImage, tolerance: u8) -> Option<RgbaImage> {
+    if actual.dimensions() != expected.dimensions() {
+        return Some(RgbaImage::from_pixel(
+            expected.width(),
+            expected.height(),
+            Rgba([255, 0, 0, 255]),
+        ));
+    }
+
+    let mut failed = false;
+    let diff = RgbaImage::from_fn(actual.width(), actual.height(), |x, y| {
+        let a = actual.get_pixel(x, y);
+        let e = expected.get_pixel(x, y);
+        let differs =
+            a.0.iter()
+                .zip(e.0.iter())
+                .any(|(a, e)| a.abs_diff(*e) > tolerance);
+        if differs {
+            failed = true;
+            Rgba([255, 0, 0, 255])
+        } else {
+            // Dimmed expected pixel, to give some context around the failures
+            let luma = (e[0] as u32 + e[1] as u32 + e[2] as u32) / 6;
+            Rgba([luma as u8, luma as u8, luma as u8, 255])
+        }
+    });
+
+    failed.then_some(diff)
+}
+
+/// Compares `actual` against the reference image called `name`,
+/// writing the actual and diff images when they do not match
+fn check_golden(name: &str, actual: &RgbaImage) {
+    let reference_path = golden_dir().join(format!("{}.png", name));
+
+    let bless = std::env::var("RAYCA_BLESS").is_ok_and(|value| value == "1");
+    if bless {
+        actual
+            .save(&reference_path)
+            .expect("Failed to write reference image");
+        eprintln!("Written reference image {}", reference_path.display());
+        return;
+    }
+
+    assert!(
+        reference_path.exists(),
+        "Missing reference image {}, run with RAYCA_BLESS=1 to write it and commit it",
+        reference_path.display()
+    );
+
+    let expected = image::open(&reference_path)
+        .expect("Failed to read reference image")
+        .into_rgba8();
+
+    if let Some(diff) = compare_images(actual, &expected, TOLERANCE) {
+        let output_dir = output_dir();
+        std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+        let actual_path = output_dir.join(format!("{}.actual.png", name));
+        let diff_path = output_dir.join(format!("{}.diff.png", name));
+        actual.save(&actual_path).expect("Failed to write image");
+        diff.save(&diff_path).expect("Failed to write diff image");
+        panic!(
+            "{} does not match its reference image, see {} and {}",
+            name,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn compare_within_tolerance() {
+    let expected = RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]));
+
+    let mut actual = expected.clone();
+    actual.put_pixel(1, 1, Rgba([100 + TOLERANCE, 100, 100, 255]));
+    assert!(compare_images(&actual, &expected, TOLERANCE).is_none());
+
+    actual.put_pixel(2, 2, Rgba([100, 100 - TOLERANCE - 1, 100, 255]));
+    let diff = compare_images(&actual, &expected, TOLERANCE).unwrap();
+    assert_eq!(*diff.get_pixel(2, 2), Rgba([255, 0, 0, 255]));
+    assert_ne!(*diff.get_pixel(1, 1), Rgba([255, 0, 0, 255]));
+
+    let smaller = RgbaImage::new(2, 2);
+    assert!(compare_images(&smaller, &expected, TOLERANCE).is_some());
+}
+
+#[test]
+fn golden_present() {
+    let actual = render(PipelinePresent::new::<PresentVertex>);
+    check_golden("triangle_present", &actual);
+}
+
+#[test]
+fn golden_glx() {
+    let actual = render_scene(
+        glx_triangle_scene,
+        PipelineUnlit::new::<Vertex>,
+        PipelinePresent::new::<PresentVertex>,
+    );
+    // Same scene as `triangle_scene`, loaded from a glx file
+    check_golden("triangle_present", &actual);
+}
+
+#[test]
+fn golden_normal() {
+    let actual = render(PipelineNormal::new::<PresentVertex>);
+    check_golden("triangle_normal", &actual);
+}
+
+#[test]
+fn golden_depth() {
+    let actual = render(PipelineDepth::new::<PresentVertex>);
+    check_golden("triangle_depth", &actual);
+}
+
+#[test]
+fn golden_pbr() {
+    let actual = render_with(
+        PipelinePbr::new::<Vertex>,
+        PipelinePresent::new::<PresentVertex>,
+    );
+    check_golden("triangle_pbr", &actual);
+}
+
+#[test]
+fn golden_lighting() {
+    let actual = render_scene(
+        lit_triangle_scene,
+        PipelineUnlit::new::<Vertex>,
+        PipelineLighting::new::<PresentVertex>,
+    );
+    check_golden("triangle_lighting", &actual);
+}
+
+#[test]
+fn golden_lighting_pbr() {
+    let actual = render_scene(
+        lit_triangle_scene,
+        PipelinePbr::new::<Vertex>,
+        PipelineLighting::new::<PresentVertex>,
+    );
+    check_golden("triangle_lighting_pbr", &actual);
+}
+
+#[test]
+fn golden_lighting_shadows() {
+    let actual = render_scene(
+        shadowed_triangle_scene,
+        PipelinePbr::new::<Vertex>,
+        PipelineLighting::new::<PresentVertex>,
+    );
+    check_golden("triangle_lighting_shadows", &actual);
+}
+
+#[test]
+fn overlapping_views() {
+    let actual = render_scene(
+        overlapping_views_scene,
+        PipelineUnlit::new::<Vertex>,
+        PipelinePresent::new::<PresentVertex>,
+    );
+    // The view on top sees nothing, so the triangle behind it does not show through
+    let background = *actual.get_pixel(0, 0);
+    assert_eq!(*actual.get_pixel(SIZE / 2, SIZE / 2), background);
+}
@@ -2,6 +2,8 @@
 // Author: Antonio Caggiano <info@antoniocaggiano.eu>
 // SPDX-License-Identifier: MIT
 
+use std::sync::Arc;
+
 use rayca_core::*;
 use rayca_pipe::*;
 
@@ -103,3 +105,781 @@ fn build_simple_shader() {
     let _main = PipelineMain::new::<Vertex>(&pass);
     let _secondary = PipelineSecondary::new::<Vertex>(&pass);
 }
+
+#[test]
+fn render_offscreen() {
+    let ctx = Ctx::builder().build();
+    let dev = Arc::new(Dev::new(&ctx, None));
+    let pass = Pass::offscreen(&dev);
+    let mut frames = OffscreenFrames::new(&dev, Size2::new(64, 64), &pass, 2);
+    let present_pipeline = PipelinePresent::new::<PresentVertex>(&pass);
+    let scene = RenderScene::new(&dev);
+
+    // Render more frames than images to make sure they are recycled
+    let mut id = 0;
+    for _ in 0..3 {
+        let mut frame = frames.next_frame().unwrap();
+        frame.begin(&scene);
+        frame.begin_render(&pass);
+        frame.set_viewport_and_scissor(1.0, false);
+        frame.draw(&scene, &[]);
+        frame.end(&scene, &present_pipeline);
+        id = frame.id;
+        frames.present(&dev, frame).unwrap();
+    }
+
+    // Float attachments are saved without losing precision
+    let frame = frames.get_frame_mut(id).unwrap();
+    let depth_path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("depth.exr");
+    frame.save_depth_exr(&depth_path).unwrap();
+    let depth = image::open(&depth_path).unwrap().into_rgba32f();
+    assert_eq!(depth.dimensions(), (64, 64));
+    // Reverse-Z clears depth to 0
+    assert_eq!(depth.get_pixel(0, 0)[0], 0.0);
+
+    // Half float attachments are read back too
+    let emission_path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("emission.exr");
+    frame.save_emission_exr(&emission_path).unwrap();
+    let emission = image::open(&emission_path).unwrap().into_rgba32f();
+    assert_eq!(emission.get_pixel(0, 0).0, [0.0; 4]);
+    // Dielectric and not occluded
+    assert_eq!(
+        frame.read_material_pixels().get_pixel(0, 0).0,
+        [0, 255, 0, 0]
+    );
+}
+
+#[test]
+fn fallible_constructors() {
+    let ctx = Ctx::builder().try_build().unwrap();
+    let dev = Dev::try_new(&ctx, None).unwrap();
+
+    let invalid_spirv = ShaderModule::try_from_data(&dev.device.device, &[0; 3]);
+    assert!(matches!(invalid_spirv, Err(Error::InvalidSpirv)));
+
+    let missing_shader = SlangProgram::get_entry_point_code("shaders/missing.slang", "main");
+    assert!(matches!(missing_shader, Err(Error::Slang(_))));
+}
+
+#[test]
+fn select_physical_device() {
+    let device_info = |index, name: &std::ffi::CStr, device_type| PhysicalDeviceInfo {
+        index,
+        physical: vk::PhysicalDevice::null(),
+        properties: vk::PhysicalDeviceProperties::default()
+            .device_name(name)
+            .unwrap()
+            .device_type(device_type),
+        features: vk::PhysicalDeviceFeatures::default().sampler_anisotropy(index == 0),
+        queue_families: vec![],
+        extensions: vec![],
+    };
+    let candidates = [
+        device_info(0, c"llvmpipe", vk::PhysicalDeviceType::CPU),
+        device_info(1, c"Integrated GPU", vk::PhysicalDeviceType::INTEGRATED_GPU),
+        device_info(2, c"Discrete GPU", vk::PhysicalDeviceType::DISCRETE_GPU),
+    ];
+    let select = |selector: DeviceSelector| selector.select(&candidates).map(|info| info.index);
+
+    assert_eq!(select(DeviceSelector::PreferDiscrete), Some(2));
+    assert_eq!(select(DeviceSelector::Index(1)), Some(1));
+    assert_eq!(select(DeviceSelector::Index(3)), None);
+    assert_eq!(select(DeviceSelector::Name("LLVMPIPE".into())), Some(0));
+
+    let anisotropy = vk::PhysicalDeviceFeatures::default().sampler_anisotropy(true);
+    assert_eq!(
+        select(DeviceSelector::requirements(anisotropy, vec![])),
+        Some(0)
+    );
+    assert_eq!(
+        select(DeviceSelector::requirements(
+            vk::PhysicalDeviceFeatures::default(),
+            vec![c"VK_KHR_swapchain"]
+        )),
+        None
+    );
+
+    // Devices picked by name or index still have to meet the requirements of the context
+    let requirements = DeviceSelector::requirements(anisotropy, vec![]);
+    assert!(requirements.meets_requirements(&candidates[0]));
+    assert!(!requirements.meets_requirements(&candidates[2]));
+    assert!(DeviceSelector::Index(2).meets_requirements(&candidates[2]));
+
+    let prefer_integrated = DeviceSelector::custom(|info| {
+        (info.get_type() == vk::PhysicalDeviceType::INTEGRATED_GPU).then_some(1)
+    });
+    assert_eq!(select(prefer_integrated), Some(1));
+}
+
+#[test]
+fn upload_image() {
+    let ctx = Ctx::builder().build();
+    let dev = Arc::new(Dev::new(&ctx, None));
+
+    let usage = vk::ImageUsageFlags::TRANSFER_DST
+        | vk::ImageUsageFlags::SAMPLED
+        | vk::ImageUsageFlags::TRANSFER_SRC;
+    let mut image = RenderImage::new(&dev.allocator, 2, 2, vk::Format::R8G8B8A8_UNORM, usage);
+    let data: Vec<u8> = (0..16).collect();
+
+    let mut uploader = Uploader::new(&dev);
+    uploader.upload_image(&mut image, &data).unwrap();
+    let mut token = uploader.submit();
+    token.wait();
+    assert!(token.is_ready());
+    assert_eq!(image.layout, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+
+    let pixels = image.read_rgba8(&dev.allocator, &dev.graphics_queue);
+    assert_eq!(pixels.into_raw(), data);
+}
+
+#[test]
+fn load_model_in_background() {
+    let ctx = Ctx::builder().build();
+    let dev = Arc::new(Dev::new(&ctx, None));
+
+    let mut loader = ModelLoader::new(
+        &dev,
+        &Assets::new(),
+        Model::default(),
+        GltfExtensions::default(),
+    );
+    while !loader.is_decoded() {
+        std::thread::yield_now();
+    }
+    let (model, mut token) = loader.try_finish().unwrap();
+    token.wait();
+    assert!(model.images.is_empty());
+}
+
+#[test]
+fn generate_mips() {
+    assert_eq!(RenderImage::get_mip_levels(1, 1), 1);
+    assert_eq!(RenderImage::get_mip_levels(256, 256), 9);
+    assert_eq!(RenderImage::get_mip_levels(300, 20), 9);
+
+    let ctx = Ctx::builder().build();
+    let dev = Arc::new(Dev::new(&ctx, None));
+
+    let format = vk::Format::R8G8B8A8_UNORM;
+    let options = ImageLoadOptions::default().generate_mips(true);
+    let data = vec![255; 16 * 8 * 4];
+    let mut uploader = Uploader::new(&dev);
+    let image = RenderImage::from_data_with_options(&mut uploader, &data, 16, 8, format, &options);
+    uploader.submit().wait();
+    if dev.device.supports_linear_blit(format) {
+        assert_eq!(image.mip_levels, 5);
+    }
+    assert_eq!(image.layout, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+    let _view = ImageView::new(&image);
+}
+
+#[test]
+fn sampler_cache() {
+    let ctx = Ctx::builder().build();
+    let dev = Dev::new(&ctx, None);
+
+    let linear = SamplerDesc {
+        mag_filter: vk::Filter::LINEAR,
+        min_filter: vk::Filter::LINEAR,
+        anisotropy: true,
+        ..Default::default()
+    };
+    let a = dev.sampler_cache.get(&linear);
+    let b = dev.sampler_cache.get(&linear);
+    assert!(Arc::ptr_eq(&a, &b));
+
+    let clamp = SamplerDesc {
+        address_mode_u: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+        address_mode_v: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+        ..linear
+    };
+    let c = dev.sampler_cache.get(&clamp);
+    assert!(!Arc::ptr_eq(&a, &c));
+    assert_eq!(dev.sampler_cache.len(), 2);
+}
+
+/// Returns a KTX2 container with the given levels of a square RGBA8 texture
+fn ktx2_rgba8(size: u32, levels: &[Vec<u8>]) -> Vec<u8> {
+    let header_size = 80 + 24 * levels.len();
+    // Only the total size of the data format descriptor
+    let dfd_size = 4;
+
+    let mut bytes = vec![
+        0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+    ];
+    let header = [
+        vk::Format::R8G8B8A8_UNORM.as_raw() as u32,
+        1, // type size
+        size,
+        size,
+        0, // depth
+        0, // layers
+        1, // faces
+        levels.len() as u32,
+        0, // supercompression
+        header_size as u32,
+        dfd_size,
+        0, // key value data
+        0,
+    ];
+    header
+        .iter()
+        .for_each(|value| bytes.extend(value.to_le_bytes()));
+    // Supercompression global data
+    bytes.extend([0; 16]);
+
+    let mut offset = (header_size + dfd_size as usize) as u64;
+    for level in levels {
+        let length = level.len() as u64;
+        [offset, length, length]
+            .iter()
+            .for_each(|value| bytes.extend(value.to_le_bytes()));
+        offset += length;
+    }
+    bytes.extend(dfd_size.to_le_bytes());
+    levels.iter().for_each(|level| bytes.extend(level));
+    bytes
+}
+
+#[test]
+fn read_material_scales() {
+    let gltf = r#"{
+        "asset": { "version": "2.0" },
+        "images": [{ "uri": "texture.png" }],
+        "textures": [{ "source": 0 }],
+        "materials": [
+            {
+                "normalTexture": { "index": 0, "scale": 0.5 },
+                "occlusionTexture": { "index": 0, "strength": 0.25 }
+            },
+            {}
+        ]
+    }"#;
+    let extensions = GltfExtensions::from_slice(gltf.as_bytes()).unwrap();
+    let scales = MaterialScales {
+        normal_scale: 0.5,
+        occlusion_strength: 0.25,
+    };
+    assert_eq!(extensions.material_scales[&0], scales);
+    assert_eq!(extensions.material_scales[&1], MaterialScales::default());
+    assert!(extensions.basisu_sources.is_empty());
+
+    // Vertices only have the first set of texture coordinates
+    let gltf = gltf.replace(
+        r#""index": 0, "scale""#,
+        r#""index": 0, "texCoord": 1, "scale""#,
+    );
+    assert!(matches!(
+        GltfExtensions::from_slice(gltf.as_bytes()),
+        Err(Error::Unsupported(_))
+    ));
+}
+
+#[test]
+fn load_gltf_with_basisu_textures() {
+    let ctx = Ctx::builder().build();
+    let dev = Arc::new(Dev::new(&ctx, None));
+
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("basisu");
+    std::fs::create_dir_all(&dir).unwrap();
+    let levels = vec![vec![255; 4 * 4 * 4], vec![128; 2 * 2 * 4], vec![0; 4]];
+    let ktx2 = ktx2_rgba8(4, &levels);
+    std::fs::write(dir.join("texture.ktx2"), &ktx2).unwrap();
+    // Basis Universal data without a data format descriptor can not be transcoded
+    let mut basis = ktx2.clone();
+    basis[12..16].copy_from_slice(&0u32.to_le_bytes());
+    std::fs::write(dir.join("basis.ktx2"), &basis).unwrap();
+    image::RgbaImage::new(2, 2)
+        .save(dir.join("fallback.png"))
+        .unwrap();
+
+    let gltf = r#"{
+        "asset": { "version": "2.0" },
+        "extensionsUsed": ["KHR_texture_basisu"],
+        "images": [
+            { "uri": "fallback.png" },
+            { "uri": "texture.ktx2", "mimeType": "image/ktx2" },
+            { "uri": "basis.ktx2", "mimeType": "image/ktx2" }
+        ],
+        "textures": [
+            { "source": 0, "extensions": { "KHR_texture_basisu": { "source": 1 } } },
+            { "source": 0, "extensions": { "KHR_texture_basisu": { "source": 2 } } }
+        ]
+    }"#;
+    std::fs::write(dir.join("basisu.gltf"), gltf).unwrap();
+
+    let model = RenderModel::load_gltf_path(&dev, &Assets::new(), dir.join("basisu.gltf"));
+    let get_source = |texture: usize| model.get_gltf().textures.get(texture.into()).unwrap().image;
+    assert_eq!(get_source(0).id, 1);
+    let image = model.images.get(1.into()).unwrap();
+    assert_eq!(image.format, vk::Format::R8G8B8A8_UNORM);
+    assert_eq!(image.mip_levels, 3);
+    // Falls back to the PNG source
+    assert_eq!(get_source(1).id, 0);
+}
+
+#[test]
+fn load_ktx2() {
+    let ctx = Ctx::builder().build();
+    let dev = Arc::new(Dev::new(&ctx, None));
+
+    let levels = vec![vec![255; 4 * 4 * 4], vec![128; 2 * 2 * 4], vec![0; 4]];
+    let bytes = ktx2_rgba8(4, &levels);
+    assert!(ImageData::is_ktx2(&bytes));
+    assert!(!ImageData::is_ktx2(b"\x89PNG\r\n\x1a\n"));
+
+    let data = ImageData::from_ktx2(&dev.device, &bytes).unwrap();
+    assert_eq!(data.format, vk::Format::R8G8B8A8_UNORM);
+    assert_eq!(data.levels, levels);
+    assert_eq!(data.get_level_offsets(), vec![0, 64, 80]);
+
+    let options = ImageLoadOptions::default().generate_mips(true);
+    let mut uploader = Uploader::new(&dev);
+    let image = RenderImage::try_from_image_data(&mut uploader, &data, &options).unwrap();
+    uploader.submit().wait();
+    assert_eq!(image.mip_levels, 3);
+    assert_eq!(image.layout, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+
+    let corrupted = &bytes[..40];
+    assert!(matches!(
+        ImageData::from_ktx2(&dev.device, corrupted),
+        Err(Error::Ktx2(_))
+    ));
+}
+
+#[test]
+fn gltf_lights() {
+    let gltf = r#"{
+        "asset": { "version": "2.0" },
+        "extensionsUsed": ["KHR_lights_punctual"],
+        "extensions": {
+            "KHR_lights_punctual": {
+                "lights": [
+                    { "type": "directional", "intensity": 3.0 },
+                    {
+                        "type": "spot",
+                        "color": [1.0, 0.5, 0.0],
+                        "range": 10.0,
+                        "spot": { "innerConeAngle": 0.25, "outerConeAngle": 0.5 }
+                    }
+                ]
+            }
+        },
+        "nodes": [
+            { "name": "Empty" },
+            { "extensions": { "KHR_lights_punctual": { "light": 1 } } },
+            { "extensions": { "KHR_lights_punctual": { "light": 0 } } }
+        ]
+    }"#;
+
+    let extensions = GltfExtensions::from_slice(gltf.as_bytes()).unwrap();
+    let lights = &extensions.lights;
+    assert_eq!(lights.len(), 2);
+
+    let (node, spot) = &lights[0];
+    assert_eq!(*node, 1);
+    assert_eq!(
+        spot.kind,
+        LightKind::Spot {
+            inner_cone_angle: 0.25,
+            outer_cone_angle: 0.5
+        }
+    );
+    assert_eq!(spot.color.g, 0.5);
+    assert_eq!(spot.intensity, 1.0);
+    assert_eq!(spot.range, Some(10.0));
+
+    let (node, directional) = &lights[1];
+    assert_eq!(*node, 2);
+    assert_eq!(directional.kind, LightKind::Directional);
+    assert_eq!(directional.intensity, 3.0);
+    assert_eq!(directional.range, None);
+
+    // Lights are attached only when the model has the same nodes as the document
+    let mut model = Model::default();
+    model.nodes.push(Node::builder().build());
+    model.nodes.push(Node::builder().build());
+    assert!(matches!(
+        extensions.get_node_handles(&model),
+        Err(Error::Model(_))
+    ));
+    let hnode = model.nodes.push(Node::builder().build());
+    assert_eq!(extensions.get_node_handles(&model).unwrap()[2], hnode);
+
+    assert!(matches!(
+        GltfExtensions::from_slice(b"{"),
+        Err(Error::Gltf(_))
+    ));
+}
+
+#[test]
+fn render_graph() {
+    let format = vk::Format::B8G8R8A8_SRGB;
+    let graph = RenderGraph::deferred(format, vk::ImageLayout::PRESENT_SRC_KHR, false);
+    assert_eq!(graph.get_pass_index("resolve"), Some(1));
+
+    let attachments = graph.try_compile_attachments().unwrap();
+    let present = &attachments[0];
+    assert!(present.desc.external);
+    assert!(!present.is_transient());
+    assert_eq!(present.final_layout, vk::ImageLayout::PRESENT_SRC_KHR);
+
+    // The G-buffer is only needed within the render pass
+    let depth = &attachments[graph.get_attachment_index(DEPTH_ATTACHMENT).unwrap()];
+    assert!(depth.is_transient());
+    assert!(depth.usage.contains(
+        vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT
+            | vk::ImageUsageFlags::INPUT_ATTACHMENT
+            | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT
+    ));
+
+    let stored = RenderGraph::deferred(format, vk::ImageLayout::TRANSFER_SRC_OPTIMAL, true)
+        .try_compile_attachments()
+        .unwrap();
+    assert!(stored.iter().all(|attachment| !attachment.is_transient()));
+
+    let unknown = RenderGraph::new()
+        .pass(PassDesc::new("geometry").color("missing"))
+        .try_compile_attachments();
+    assert!(matches!(unknown, Err(Error::InvalidRenderGraph(_))));
+}
+
+#[test]
+fn attachment_clear_values() {
+    let format = vk::Format::B8G8R8A8_SRGB;
+    let graph = RenderGraph::deferred(format, vk::ImageLayout::PRESENT_SRC_KHR, false);
+    let present = &graph.attachments[graph.get_attachment_index(PRESENT_ATTACHMENT).unwrap()];
+    let depth = &graph.attachments[graph.get_attachment_index(DEPTH_ATTACHMENT).unwrap()];
+
+    // A transparent background replaces the clear color of the pass
+    let background = Some(Color::new(1.0, 0.5, 0.25, 0.0));
+    let clear = present.get_clear_value(background);
+    assert_eq!(unsafe { clear.color.float32 }, [1.0, 0.5, 0.25, 0.0]);
+    let clear = present.get_clear_value(None);
+    assert_eq!(
+        unsafe { clear.color.float32 },
+        [0.0, 10.0 / 255.0, 28.0 / 255.0, 1.0]
+    );
+    let clear = depth.get_clear_value(background);
+    assert_eq!(unsafe { clear.depth_stencil.depth }, 0.0);
+
+    assert_eq!(present.get_load_op(), vk::AttachmentLoadOp::CLEAR);
+    let loaded = present.clone().load();
+    assert_eq!(loaded.get_load_op(), vk::AttachmentLoadOp::LOAD);
+    assert_eq!(
+        loaded.dont_care().get_load_op(),
+        vk::AttachmentLoadOp::DONT_CARE
+    );
+}
+
+#[test]
+fn reverse_z_projections() {
+    // Depth of a point at `distance` in front of the camera, which looks along -Z
+    let get_depth = |projection: CameraProjection, distance: f32| {
+        let rows: [[f32; 4]; 4] = unsafe { std::mem::transmute_copy(&projection.get_matrix(2.0)) };
+        let z = rows[2][2] * -distance + rows[2][3];
+        let w = rows[3][2] * -distance + rows[3][3];
+        z / w
+    };
+
+    let infinite = CameraProjection::perspective(1.0, 0.1);
+    assert!((get_depth(infinite, 0.1) - 1.0).abs() < 1e-6);
+    assert!(get_depth(infinite, 1e6) > 0.0);
+    assert!(get_depth(infinite, 1.0) > get_depth(infinite, 2.0));
+
+    let finite = infinite.far(100.0);
+    assert!((get_depth(finite, 0.1) - 1.0).abs() < 1e-6);
+    assert!(get_depth(finite, 100.0).abs() < 1e-6);
+
+    let orthographic = CameraProjection::orthographic(2.0, 1.0, 11.0);
+    assert!((get_depth(orthographic, 1.0) - 1.0).abs() < 1e-6);
+    assert!((get_depth(orthographic, 6.0) - 0.5).abs() < 1e-6);
+    assert!(get_depth(orthographic, 11.0).abs() < 1e-6);
+
+    // The inverse maps normalized device coordinates back to the same view position
+    for projection in [infinite, finite, orthographic] {
+        let rows: [[f32; 4]; 4] = unsafe { std::mem::transmute_copy(&projection.get_matrix(2.0)) };
+        let inverse: [[f32; 4]; 4] =
+            unsafe { std::mem::transmute_copy(&projection.get_inverse_matrix(2.0)) };
+        let position = [0.5, -0.25, -3.0, 1.0];
+        let clip: [f32; 4] =
+            std::array::from_fn(|r| (0..4).map(|c| rows[r][c] * position[c]).sum());
+        let view: [f32; 4] = std::array::from_fn(|r| (0..4).map(|c| inverse[r][c] * clip[c]).sum());
+        for (value, expected) in view.iter().zip(position) {
+            assert!((value / view[3] - expected).abs() < 1e-4);
+        }
+    }
+}
+
+#[test]
+fn gltf_camera_projections() {
+    let (yfov, near, far) = (1.0f32, 0.1, 100.0);
+    let f = 1.0 / (yfov * 0.5).tan();
+    let rows = [
+        [f / 1.5, 0.0, 0.0, 0.0],
+        [0.0, f, 0.0, 0.0],
+        [
+            0.0,
+            0.0,
+            (far + near) / (near - far),
+            2.0 * far * near / (near - far),
+        ],
+        [0.0, 0.0, -1.0, 0.0],
+    ];
+    let columns: [[f32; 4]; 4] = std::array::from_fn(|c| std::array::from_fn(|r| rows[r][c]));
+    for matrix in [rows, columns] {
+        let matrix: Mat4 = unsafe { std::mem::transmute_copy(&matrix) };
+        let Some(CameraProjection::Perspective {
+            yfov: y,
+            near: n,
+            far: Some(f),
+        }) = CameraProjection::from_gltf_matrix(&matrix)
+        else {
+            panic!("Expected a finite perspective projection");
+        };
+        assert!((y - yfov).abs() < 1e-5 && (n - near).abs() < 1e-5 && (f - far).abs() < 1e-2);
+    }
+
+    let infinite = [
+        [f, 0.0, 0.0, 0.0],
+        [0.0, f, 0.0, 0.0],
+        [0.0, 0.0, -1.0, -2.0 * near],
+        [0.0, 0.0, -1.0, 0.0],
+    ];
+    let infinite: Mat4 = unsafe { std::mem::transmute_copy(&infinite) };
+    let projection = CameraProjection::from_gltf_matrix(&infinite).unwrap();
+    assert!(matches!(
+        projection,
+        CameraProjection::Perspective { far: None, .. }
+    ));
+
+    let orthographic: [[f32; 4]; 4] = [
+        [0.5, 0.0, 0.0, 0.0],
+        [0.0, 0.5, 0.0, 0.0],
+        [0.0, 0.0, 2.0 / (1.0 - 11.0), 12.0 / (1.0 - 11.0)],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    let orthographic: Mat4 = unsafe { std::mem::transmute_copy(&orthographic) };
+    let projection = CameraProjection::from_gltf_matrix(&orthographic).unwrap();
+    let CameraProjection::Orthographic { ymag, near, far } = projection else {
+        panic!("Expected an orthographic projection");
+    };
+    assert!((ymag - 2.0).abs() < 1e-6 && (near - 1.0).abs() < 1e-6 && (far - 11.0).abs() < 1e-5);
+
+    // Matrices which do not project anything are not glTF projections
+    let identity: [[f32; 4]; 4] =
+        std::array::from_fn(|r| std::array::from_fn(|c| (r == c) as u8 as f32));
+    let identity: Mat4 = unsafe { std::mem::transmute_copy(&identity) };
+    assert!(CameraProjection::from_gltf_matrix(&identity).is_none());
+}
+
+#[test]
+fn split_screen_views() {
+    let size = Size2::new(640, 480);
+    let left = CameraView::new(CameraDrawInfo::default()).extent(0.5, 1.0);
+    let right = left.offset(0.5, 0.0);
+
+    let rect = right.get_rect(size);
+    assert_eq!((rect.offset.x, rect.offset.y), (320, 0));
+    assert_eq!((rect.extent.width, rect.extent.height), (320, 480));
+    assert_eq!(left.get_aspect(size), 320.0 / 480.0);
+
+    // Views going past the frame are clamped to it
+    let minimap = CameraView::new(CameraDrawInfo::default())
+        .offset(0.9, 0.9)
+        .extent(0.25, 0.25);
+    let rect = minimap.get_rect(size);
+    assert_eq!((rect.extent.width, rect.extent.height), (64, 48));
+}
+
+#[test]
+fn camera_controllers() {
+    let mut trs = Trs::builder().build();
+    let mut input = Input::default();
+    input.set_key(KeyCode::KeyW, true);
+
+    // Holding a key moves the same distance whatever the frame rate
+    let mut slow = FlyController::default().speed(2.0);
+    slow.update(&input, std::time::Duration::from_millis(100), &mut trs);
+    let mut fast = FlyController::default().speed(2.0);
+    for _ in 0..10 {
+        fast.update(&input, std::time::Duration::from_millis(10), &mut trs);
+    }
+    assert!((slow.position.z + 0.2).abs() < 1e-5);
+    assert!((slow.position.z - fast.position.z).abs() < 1e-5);
+
+    // Orbiting keeps the camera at the same distance from the target
+    let mut orbit = OrbitController::default()
+        .target(1.0, 0.0, 0.0)
+        .distance(3.0);
+    let mut input = Input::default();
+    input.mouse.set_button(MouseButton::Left, true);
+    input.mouse.movement = Vec2::new(100.0, 50.0);
+    orbit.update(&input, std::time::Duration::ZERO, &mut trs);
+    let distance = (orbit.get_position() - orbit.target).len();
+    assert!((distance - 3.0).abs() < 1e-5);
+
+    // Scrolling up gets closer
+    input.mouse.scroll = Vec2::new(0.0, 1.0);
+    orbit.update(&input, std::time::Duration::ZERO, &mut trs);
+    assert!(orbit.distance < 3.0);
+
+    // Controllers created from the node of a camera start where it is
+    let fly = FlyController::default()
+        .position(1.0, 2.0, 3.0)
+        .yaw(0.5)
+        .pitch(0.25);
+    let resumed = FlyController::from_trs(&fly.get_trs());
+    assert!((resumed.position - fly.position).len() < 1e-5);
+    assert!((resumed.yaw - fly.yaw).abs() < 1e-5);
+    assert!((resumed.pitch - fly.pitch).abs() < 1e-5);
+}
+
+#[test]
+fn input_key_states() {
+    let mut input = Input::default();
+    input.set_key(KeyCode::Space, true);
+    input.text.push('a');
+    assert_eq!(input.get_key(KeyCode::Space), ButtonState::JustPressed);
+
+    // Repeated presses of a held key do not restart it
+    input.update();
+    input.set_key(KeyCode::Space, true);
+    assert_eq!(input.get_key(KeyCode::Space), ButtonState::Pressed);
+    assert!(input.text.is_empty());
+
+    input.set_key(KeyCode::Space, false);
+    assert!(input.get_key(KeyCode::Space).just_updated());
+    input.update();
+    assert!(input.keys.is_empty());
+    assert!(!input.is_key_down(KeyCode::Space));
+
+    // Losing focus releases whatever is held, as its release would never arrive
+    input.set_key(KeyCode::KeyW, true);
+    input.mouse.set_button(MouseButton::Left, true);
+    input.release_all();
+    assert_eq!(input.get_key(KeyCode::KeyW), ButtonState::JustReleased);
+    assert_eq!(
+        input.mouse.get_button(MouseButton::Left),
+        ButtonState::JustReleased
+    );
+}
+
+#[test]
+fn action_map() {
+    let map = ActionMap::default()
+        .action("jump", Binding::Key(KeyCode::Space))
+        .action("jump", Binding::Android(AndroidKeyCode::A))
+        .axis(
+            "move_forward",
+            AxisBinding::buttons(Binding::Key(KeyCode::KeyS), Binding::Key(KeyCode::KeyW)),
+        )
+        .axis(
+            "move_forward",
+            AxisBinding::analog(AxisSource::AndroidLeftY).scale(-1.0),
+        );
+
+    let mut input = Input::default();
+    input.android.a.press();
+    input.set_key(KeyCode::KeyW, true);
+    assert_eq!(map.get_action("jump", &input), ButtonState::JustPressed);
+    assert_eq!(map.get_axis("move_forward", &input), 1.0);
+    assert_eq!(map.get_action("unbound", &input), ButtonState::Released);
+
+    // The most advanced state wins when several bindings are active
+    input.update();
+    input.set_key(KeyCode::Space, true);
+    assert_eq!(map.get_action("jump", &input), ButtonState::Pressed);
+
+    let mut rebound = ActionMap::try_from_json(&map.to_json()).unwrap();
+    assert_eq!(rebound, map);
+    rebound.rebind_action("jump", vec![Binding::Mouse(MouseButton::Left)]);
+    assert!(!rebound.is_action_down("jump", &input));
+}
+
+#[test]
+fn gamepad_input() {
+    let mut input = Input::default();
+    let gamepad = input.get_gamepad_mut(3, "Pad");
+    gamepad.set_button(GamepadButton::South, true);
+    gamepad.set_axis(GamepadAxis::LeftStickY, -0.75);
+    gamepad.set_axis(GamepadAxis::RightTrigger, 0.8);
+    assert_eq!(input.gamepad_events, [GamepadEvent::Connected(3)]);
+
+    let map = ActionMap::default()
+        .action("jump", Binding::Gamepad(GamepadButton::South))
+        .action("fire", Binding::Gamepad(GamepadButton::RightTrigger))
+        .axis(
+            "move_forward",
+            AxisBinding::analog(AxisSource::Gamepad(GamepadAxis::LeftStickY)),
+        );
+    assert_eq!(map.get_action("jump", &input), ButtonState::JustPressed);
+    assert!(map.is_action_down("fire", &input));
+    assert_eq!(map.get_axis("move_forward", &input), -0.75);
+
+    input.update();
+    assert!(input.gamepad_events.is_empty());
+    input.remove_gamepad(3);
+    assert_eq!(input.gamepad_events, [GamepadEvent::Disconnected(3)]);
+    assert!(!map.is_action_down("jump", &input));
+
+    // Android joysticks feed the left stick of the first gamepad, with Y pointing up
+    input.set_android_joystick(Vec2::new(0.5, 0.25));
+    assert_eq!(input.android.left_axis, Vec2::new(0.5, 0.25));
+    input.get_gamepad_mut(5, "Joystick");
+    input.set_android_joystick(Vec2::new(0.5, 0.25));
+    assert_eq!(map.get_axis("move_forward", &input), -0.25);
+}
+
+#[test]
+fn touch_gestures() {
+    let start = std::time::Instant::now();
+    let at = |ms| start + std::time::Duration::from_millis(ms);
+    let mut touches = Touches::default();
+
+    // Two quick taps at the same place make a double tap
+    touches.handle(0, TouchPhase::Started, Vec2::new(10.0, 10.0), at(0));
+    touches.handle(0, TouchPhase::Ended, Vec2::new(12.0, 10.0), at(100));
+    touches.handle(1, TouchPhase::Started, Vec2::new(11.0, 10.0), at(200));
+    touches.handle(1, TouchPhase::Ended, Vec2::new(11.0, 10.0), at(250));
+    assert!(matches!(
+        touches.gestures[..],
+        [
+            Gesture::Tap { .. },
+            Gesture::Tap { .. },
+            Gesture::DoubleTap { .. }
+        ]
+    ));
+    touches.update();
+    assert!(touches.fingers.is_empty());
+
+    // Moving past the slop is a drag, not a tap
+    touches.handle(2, TouchPhase::Started, Vec2::new(0.0, 0.0), at(1000));
+    touches.handle(2, TouchPhase::Moved, Vec2::new(30.0, 0.0), at(1010));
+    touches.handle(2, TouchPhase::Ended, Vec2::new(40.0, 0.0), at(1020));
+    assert_eq!(touches.get_drag().x, 40.0);
+    assert!(
+        !touches
+            .gestures
+            .iter()
+            .any(|g| matches!(g, Gesture::Tap { .. }))
+    );
+    touches.update();
+
+    // Spreading two fingers pinches, and turning them rotates
+    touches.handle(3, TouchPhase::Started, Vec2::new(0.0, 0.0), at(2000));
+    touches.handle(4, TouchPhase::Started, Vec2::new(10.0, 0.0), at(2000));
+    touches.handle(4, TouchPhase::Moved, Vec2::new(0.0, 20.0), at(2010));
+    assert!((touches.get_pinch() - 2.0).abs() < 1e-5);
+    let angle = touches.gestures.iter().find_map(|g| match g {
+        Gesture::Rotate { angle, .. } => Some(*angle),
+        _ => None,
+    });
+    assert!((angle.unwrap() - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+    assert_eq!(touches.get_drag(), Vec2::ZERO);
+
+    // Cancelled fingers are no longer active
+    touches.handle(3, TouchPhase::Cancelled, Vec2::new(0.0, 0.0), at(2020));
+    assert_eq!(touches.get_active_count(), 1);
+}